@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use fruityger::format::{AudioFormat, CoverFormat};
+
+fn audio_format_benchmark(c: &mut Criterion) {
+    c.bench_function("audio_format_mime", |b| {
+        b.iter(|| AudioFormat::try_from(black_box("audio/flac")))
+    });
+    c.bench_function("audio_format_extension", |b| {
+        b.iter(|| AudioFormat::try_from(black_box("/tmp/audio.m4a")))
+    });
+    c.bench_function("audio_format_unsupported", |b| {
+        b.iter(|| AudioFormat::try_from(black_box("application/octet-stream")))
+    });
+}
+
+fn cover_format_benchmark(c: &mut Criterion) {
+    c.bench_function("cover_format_mime", |b| {
+        b.iter(|| CoverFormat::try_from(black_box("image/png")))
+    });
+    c.bench_function("cover_format_extension", |b| {
+        b.iter(|| CoverFormat::try_from(black_box("/tmp/cover.jpg")))
+    });
+}
+
+criterion_group!(benches, audio_format_benchmark, cover_format_benchmark);
+criterion_main!(benches);
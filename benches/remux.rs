@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+use fruityger::{Metadata, cover::CoverArtType, format::AudioFormat, remux};
+use std::path::{Path, PathBuf};
+
+fn remux_benchmark(c: &mut Criterion) {
+    let audio_path =
+        PathBuf::from(std::env::var("AUDIO_PATH").unwrap_or("/tmp/audio.flac".to_owned()));
+    let cover_path =
+        PathBuf::from(std::env::var("COVER_PATH").unwrap_or("/tmp/cover.jpg".to_owned()));
+    let size = std::fs::metadata(&audio_path)
+        .expect("AUDIO_PATH is required to run this benchmark")
+        .len();
+
+    let mut group = c.benchmark_group("remux");
+    group.throughput(Throughput::Bytes(size));
+    group.bench_function("flac_with_cover", |b| {
+        b.iter(|| {
+            remux(
+                Path::new("/tmp"),
+                black_box(&audio_path),
+                vec![(black_box(cover_path.clone()), CoverArtType::FrontCover)],
+                AudioFormat::Flac,
+                "remux_bench",
+                Metadata {
+                    title: "remux bench".to_owned(),
+                    artist: "fruityger".to_owned(),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, remux_benchmark);
+criterion_main!(benches);
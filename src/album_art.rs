@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use image::{DynamicImage, ImageFormat, imageops::FilterType};
+
+use crate::error::Error;
+
+/// Fingerprints at most this many bits apart are considered the same cover,
+/// which absorbs differences from resizing and JPEG re-encoding
+pub const FINGERPRINT_THRESHOLD: u32 = 5;
+
+/// Average hash: the image is reduced to 8x8 grayscale and every pixel
+/// brighter than the mean sets its bit, row by row from the top left
+pub fn average_hash(image: &DynamicImage) -> u64 {
+    let pixels = image.resize_exact(8, 8, FilterType::Triangle).into_luma8();
+    let mean = pixels.iter().map(|&p| p as u32).sum::<u32>() / 64;
+    pixels
+        .iter()
+        .fold(0, |hash, &p| hash << 1 | (p as u32 > mean) as u64)
+}
+
+/// Decodes a cover, AVIF fails with [`Error::UnsupportedFormatError`]
+/// since decoding it would need the native dav1d library
+pub(crate) fn decode_cover(data: &[u8]) -> Result<DynamicImage, Error> {
+    if image::guess_format(data).is_ok_and(|f| f == ImageFormat::Avif) {
+        return Err(Error::UnsupportedFormatError);
+    }
+    Ok(image::load_from_memory(data)?)
+}
+
+/// Perceptual fingerprint of an encoded cover image
+pub fn cover_art_fingerprint(data: &[u8]) -> Result<u64, Error> {
+    Ok(average_hash(&decode_cover(data)?))
+}
+
+pub fn is_same_cover(a: u64, b: u64) -> bool {
+    (a ^ b).count_ones() <= FINGERPRINT_THRESHOLD
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::io::Cursor;
+
+    use image::{DynamicImage, ImageFormat, RgbImage};
+
+    use crate::{
+        album_art::{average_hash, cover_art_fingerprint, is_same_cover},
+        error::Error,
+    };
+
+    pub(crate) fn cover(size: u32, invert: bool, format: ImageFormat) -> Vec<u8> {
+        let image = RgbImage::from_fn(size, size, |x, y| {
+            let value = ((x + y) * 255 / (size * 2)) as u8;
+            let value = if invert { 255 - value } else { value };
+            [value, value / 2, 255 - value].into()
+        });
+        let mut data = Cursor::new(vec![]);
+        DynamicImage::from(image)
+            .write_to(&mut data, format)
+            .unwrap();
+        data.into_inner()
+    }
+
+    #[test]
+    fn fingerprint() {
+        let small = cover_art_fingerprint(&cover(200, false, ImageFormat::Jpeg)).unwrap();
+        let large = cover_art_fingerprint(&cover(400, false, ImageFormat::Png)).unwrap();
+        let inverted = cover_art_fingerprint(&cover(200, true, ImageFormat::Jpeg)).unwrap();
+        assert!(is_same_cover(small, large));
+        assert!(!is_same_cover(small, inverted));
+        assert_eq!(average_hash(&DynamicImage::new_luma8(8, 8)), 0);
+        assert!(cover_art_fingerprint(b"not an image").is_err());
+
+        let webp = cover_art_fingerprint(&cover(200, false, ImageFormat::WebP)).unwrap();
+        assert!(is_same_cover(small, webp));
+        assert!(matches!(
+            cover_art_fingerprint(b"\0\0\0\x1cftypavif\0\0\0\0avifmif1miaf"),
+            Err(Error::UnsupportedFormatError)
+        ));
+    }
+}
@@ -0,0 +1,255 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::{
+    ffi::{CString, c_int, c_void},
+    io::{Read, Seek, SeekFrom, Write},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    ptr, slice,
+};
+
+use ffmpeg_next::{
+    error::ENOMEM,
+    ffi::{
+        AVERROR_EOF, AVERROR_EXTERNAL, AVFMT_FLAG_CUSTOM_IO, AVIOContext, AVSEEK_FORCE,
+        AVSEEK_SIZE, av_free, av_freep, av_malloc, avformat_alloc_context,
+        avformat_alloc_output_context2, avformat_close_input, avformat_find_stream_info,
+        avformat_open_input, avio_alloc_context, avio_context_free,
+    },
+    format::context::{Input, Output},
+};
+
+use crate::error::Error;
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+const SEEK_SET: c_int = 0;
+const SEEK_CUR: c_int = 1;
+const SEEK_END: c_int = 2;
+
+unsafe extern "C" fn read_packet<T: Read>(
+    opaque: *mut c_void,
+    buf: *mut u8,
+    buf_size: c_int,
+) -> c_int {
+    let (inner, buf) = unsafe {
+        (
+            &mut *(opaque as *mut T),
+            slice::from_raw_parts_mut(buf, buf_size as usize),
+        )
+    };
+    match inner.read(buf) {
+        Ok(0) => AVERROR_EOF,
+        Ok(n) => n as c_int,
+        Err(_) => AVERROR_EXTERNAL,
+    }
+}
+
+unsafe extern "C" fn write_packet<T: Write>(
+    opaque: *mut c_void,
+    buf: *const u8,
+    buf_size: c_int,
+) -> c_int {
+    let (inner, buf) = unsafe {
+        (
+            &mut *(opaque as *mut T),
+            slice::from_raw_parts(buf, buf_size as usize),
+        )
+    };
+    match inner.write_all(buf) {
+        Ok(()) => buf_size,
+        Err(_) => AVERROR_EXTERNAL,
+    }
+}
+
+unsafe extern "C" fn seek<T: Seek>(opaque: *mut c_void, offset: i64, whence: c_int) -> i64 {
+    let inner = unsafe { &mut *(opaque as *mut T) };
+    let pos = match whence & !(AVSEEK_FORCE as c_int) {
+        SEEK_SET => SeekFrom::Start(offset as u64),
+        SEEK_CUR => SeekFrom::Current(offset),
+        SEEK_END => SeekFrom::End(offset),
+        w if w == AVSEEK_SIZE as c_int => {
+            let size = inner.stream_position().and_then(|current| {
+                let size = inner.seek(SeekFrom::End(0))?;
+                inner.seek(SeekFrom::Start(current))?;
+                Ok(size)
+            });
+            return size.map_or(AVERROR_EXTERNAL as i64, |size| size as i64);
+        }
+        _ => return AVERROR_EXTERNAL as i64,
+    };
+    inner
+        .seek(pos)
+        .map_or(AVERROR_EXTERNAL as i64, |pos| pos as i64)
+}
+
+/// Custom AVIO context backed by a borrowed Rust reader or writer
+pub struct IoContext<'a, T> {
+    ptr: *mut AVIOContext,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> IoContext<'a, T> {
+    fn new(
+        inner: &'a mut T,
+        write: bool,
+        read_packet: Option<unsafe extern "C" fn(*mut c_void, *mut u8, c_int) -> c_int>,
+        write_packet: Option<unsafe extern "C" fn(*mut c_void, *const u8, c_int) -> c_int>,
+        seek: Option<unsafe extern "C" fn(*mut c_void, i64, c_int) -> i64>,
+    ) -> Result<Self, Error> {
+        unsafe {
+            let buffer = av_malloc(BUFFER_SIZE) as *mut u8;
+            if buffer.is_null() {
+                return Err(Error::RemuxError(ffmpeg_next::Error::Other {
+                    errno: ENOMEM,
+                }));
+            }
+            let ptr = avio_alloc_context(
+                buffer,
+                BUFFER_SIZE as c_int,
+                write as c_int,
+                inner as *mut T as *mut c_void,
+                read_packet,
+                write_packet,
+                seek,
+            );
+            if ptr.is_null() {
+                av_free(buffer as *mut c_void);
+                return Err(Error::RemuxError(ffmpeg_next::Error::Other {
+                    errno: ENOMEM,
+                }));
+            }
+            Ok(Self {
+                ptr,
+                _marker: PhantomData,
+            })
+        }
+    }
+}
+
+impl<'a, T: Read + Seek> IoContext<'a, T> {
+    pub fn reader(inner: &'a mut T) -> Result<Self, Error> {
+        Self::new(inner, false, Some(read_packet::<T>), None, Some(seek::<T>))
+    }
+}
+
+impl<'a, T: Write + Seek> IoContext<'a, T> {
+    pub fn writer(inner: &'a mut T) -> Result<Self, Error> {
+        Self::new(inner, true, None, Some(write_packet::<T>), Some(seek::<T>))
+    }
+}
+
+impl<T> Drop for IoContext<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            // The buffer may have been reallocated by FFmpeg, free whatever it points to now
+            av_freep(&mut (*self.ptr).buffer as *mut _ as *mut c_void);
+            avio_context_free(&mut self.ptr);
+        }
+    }
+}
+
+/// Demuxer reading through an [`IoContext`], dropped before the context itself
+pub struct MemoryInput<'a, T> {
+    input: Input,
+    _io: IoContext<'a, T>,
+}
+
+impl<'a, T: Read + Seek> MemoryInput<'a, T> {
+    pub fn new(io: IoContext<'a, T>) -> Result<Self, Error> {
+        unsafe {
+            let mut ps = avformat_alloc_context();
+            if ps.is_null() {
+                return Err(Error::RemuxError(ffmpeg_next::Error::Other {
+                    errno: ENOMEM,
+                }));
+            }
+            // Setting pb before opening makes FFmpeg mark the context with
+            // AVFMT_FLAG_CUSTOM_IO, so closing the input leaves pb alone
+            (*ps).pb = io.ptr;
+            match avformat_open_input(&mut ps, ptr::null(), ptr::null(), ptr::null_mut()) {
+                0 => match avformat_find_stream_info(ps, ptr::null_mut()) {
+                    r if r >= 0 => Ok(Self {
+                        input: Input::wrap(ps),
+                        _io: io,
+                    }),
+                    e => {
+                        avformat_close_input(&mut ps);
+                        Err(Error::RemuxError(e.into()))
+                    }
+                },
+                e => Err(Error::RemuxError(e.into())),
+            }
+        }
+    }
+}
+
+impl<T> Deref for MemoryInput<'_, T> {
+    type Target = Input;
+
+    fn deref(&self) -> &Self::Target {
+        &self.input
+    }
+}
+
+impl<T> DerefMut for MemoryInput<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.input
+    }
+}
+
+/// Muxer writing through an [`IoContext`], dropped before the context itself
+pub struct MemoryOutput<'a, T> {
+    output: Output,
+    _io: IoContext<'a, T>,
+}
+
+impl<'a, T: Write + Seek> MemoryOutput<'a, T> {
+    pub fn new(io: IoContext<'a, T>, format_name: &str) -> Result<Self, Error> {
+        let format_name = CString::new(format_name).unwrap();
+        unsafe {
+            let mut ps = ptr::null_mut();
+            match avformat_alloc_output_context2(
+                &mut ps,
+                ptr::null(),
+                format_name.as_ptr(),
+                ptr::null(),
+            ) {
+                r if r >= 0 => {
+                    (*ps).pb = io.ptr;
+                    (*ps).flags |= AVFMT_FLAG_CUSTOM_IO as c_int;
+                    Ok(Self {
+                        output: Output::wrap(ps),
+                        _io: io,
+                    })
+                }
+                e => Err(Error::RemuxError(e.into())),
+            }
+        }
+    }
+}
+
+impl<T> Drop for MemoryOutput<'_, T> {
+    fn drop(&mut self) {
+        // Output calls avio_close on pb when dropped, which must not happen
+        // for a context that was not opened with avio_open
+        unsafe {
+            (*self.output.as_mut_ptr()).pb = ptr::null_mut();
+        }
+    }
+}
+
+impl<T> Deref for MemoryOutput<'_, T> {
+    type Target = Output;
+
+    fn deref(&self) -> &Self::Target {
+        &self.output
+    }
+}
+
+impl<T> DerefMut for MemoryOutput<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.output
+    }
+}
@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use image::GenericImageView;
+
+use crate::{
+    SearchResults,
+    album_art::{average_hash, decode_cover, is_same_cover},
+    error::Error,
+};
+
+pub const DEFAULT_SEARCH_TTL: Duration = Duration::from_secs(60);
+
+/// Keeps search results per query and page, so repeating a search within
+/// the TTL does not hit the service again
+pub struct SearchCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<(String, usize), (SearchResults, Instant)>>,
+}
+
+impl Default for SearchCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEARCH_TTL)
+    }
+}
+
+impl SearchCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns cached results for `query` and `page` if they have not expired,
+    /// otherwise calls `fetch` and caches what it returns. Pages are cached
+    /// separately, a cached first page does not prevent fetching the second.
+    pub async fn search<F, Fut>(
+        &self,
+        query: &str,
+        page: usize,
+        fetch: F,
+    ) -> Result<SearchResults, Error>
+    where
+        F: FnOnce(&str, usize) -> Fut,
+        Fut: Future<Output = Result<SearchResults, Error>>,
+    {
+        let key = (query.to_lowercase(), page);
+        if let Some((results, _)) = self
+            .entries
+            .lock()
+            .unwrap()
+            .get(&key)
+            .filter(|(_, fetched_at)| fetched_at.elapsed() < self.ttl)
+        {
+            return Ok(SearchResults {
+                cache_hit: true,
+                ..results.clone()
+            });
+        }
+
+        let results = fetch(query, page).await?;
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (_, fetched_at)| fetched_at.elapsed() < self.ttl);
+        entries.insert(key, (results.clone(), Instant::now()));
+        Ok(results)
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+struct CachedCover {
+    fingerprint: u64,
+    pixels: u64,
+    data: Bytes,
+}
+
+/// Keeps cover art by perceptual fingerprint instead of URL, so the same
+/// cover at different resolutions is only stored once, at the highest one
+#[derive(Default)]
+pub struct CoverCache {
+    entries: Mutex<Vec<CachedCover>>,
+}
+
+impl CoverCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `data` unless a larger version of the same cover is already
+    /// cached, returns the fingerprint to look the cover up with
+    pub fn insert(&self, data: Bytes) -> Result<u64, Error> {
+        let image = decode_cover(&data)?;
+        let fingerprint = average_hash(&image);
+        let (width, height) = image.dimensions();
+        let pixels = width as u64 * height as u64;
+
+        let mut entries = self.entries.lock().unwrap();
+        match entries
+            .iter_mut()
+            .find(|e| is_same_cover(e.fingerprint, fingerprint))
+        {
+            Some(entry) => {
+                if pixels > entry.pixels {
+                    entry.pixels = pixels;
+                    entry.data = data;
+                }
+                Ok(entry.fingerprint)
+            }
+            None => {
+                entries.push(CachedCover {
+                    fingerprint,
+                    pixels,
+                    data,
+                });
+                Ok(fingerprint)
+            }
+        }
+    }
+
+    pub fn get(&self, fingerprint: u64) -> Option<Bytes> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|e| is_same_cover(e.fingerprint, fingerprint))
+            .map(|e| e.data.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use image::ImageFormat;
+
+    use crate::{
+        SearchResults,
+        album_art::test::cover,
+        cache::{CoverCache, SearchCache},
+    };
+
+    #[tokio::test]
+    async fn search() {
+        let cache = SearchCache::default();
+        let fetches = AtomicUsize::new(0);
+        let fetch = |_: &str, page: usize| {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            async move { Ok(SearchResults::example(page + 1)) }
+        };
+
+        let results = cache.search("Query", 0, fetch).await.unwrap();
+        assert!(!results.cache_hit);
+        let results = cache.search("query", 0, fetch).await.unwrap();
+        assert!(results.cache_hit);
+        assert_eq!(results.tracks.len(), 1);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        let results = cache.search("query", 1, fetch).await.unwrap();
+        assert!(!results.cache_hit);
+        assert_eq!(results.tracks.len(), 2);
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+        let cache = SearchCache::new(Duration::ZERO);
+        cache.search("query", 0, fetch).await.unwrap();
+        assert!(!cache.search("query", 0, fetch).await.unwrap().cache_hit);
+        assert_eq!(fetches.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn cover_cache() {
+        let cache = CoverCache::new();
+        let small = cover(200, false, ImageFormat::Jpeg);
+        let large = cover(400, false, ImageFormat::Jpeg);
+        let fingerprint = cache.insert(small.clone().into()).unwrap();
+        assert_eq!(cache.insert(large.clone().into()).unwrap(), fingerprint);
+        assert_eq!(cache.insert(small.into()).unwrap(), fingerprint);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(fingerprint).unwrap(), large);
+
+        cache
+            .insert(cover(200, true, ImageFormat::Png).into())
+            .unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.insert(b"not an image".as_slice().into()).is_err());
+    }
+}
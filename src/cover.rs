@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::format::AudioFormat;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoverArtType {
+    FrontCover,
+    BackCover,
+    ArtistPhoto,
+    Illustration,
+    /// Picture with a free-form description
+    Other(String),
+}
+
+impl CoverArtType {
+    /// ID3v2 APIC picture type, FLAC PICTURE blocks use the same values
+    pub fn picture_type(&self) -> u8 {
+        match self {
+            Self::FrontCover => 3,
+            Self::BackCover => 4,
+            Self::ArtistPhoto => 8,
+            Self::Illustration => 18,
+            Self::Other(_) => 0,
+        }
+    }
+
+    /// Stream comment that FFmpeg's FLAC and MP3 muxers map to the picture type
+    pub(crate) fn ffmpeg_comment(&self) -> &'static str {
+        match self {
+            Self::FrontCover => "Cover (front)",
+            Self::BackCover => "Cover (back)",
+            Self::ArtistPhoto => "Artist/performer",
+            Self::Illustration => "Illustration",
+            Self::Other(_) => "Other",
+        }
+    }
+
+    /// MP4 only has a single cover atom, picture types are lost there. The
+    /// Ogg, WAV and AIFF muxers cannot carry attached pictures at all.
+    pub(crate) fn is_supported_by(&self, format: &AudioFormat) -> bool {
+        match format {
+            AudioFormat::Flac | AudioFormat::Mp3(_) => true,
+            AudioFormat::FlacMp4 | AudioFormat::Aac(_) | AudioFormat::Alac => {
+                *self == Self::FrontCover
+            }
+            AudioFormat::Opus(_)
+            | AudioFormat::Vorbis(_)
+            | AudioFormat::Wav
+            | AudioFormat::Aiff => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemuxWarning {
+    /// The cover was left out because the target container cannot tell it
+    /// apart from the front cover
+    CoverTypeNotSupported(CoverArtType),
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{cover::CoverArtType, format::AudioFormat};
+
+    #[test]
+    fn is_supported_by() {
+        assert!(CoverArtType::BackCover.is_supported_by(&AudioFormat::Flac));
+        assert!(CoverArtType::FrontCover.is_supported_by(&AudioFormat::Aac(256)));
+        assert!(!CoverArtType::ArtistPhoto.is_supported_by(&AudioFormat::Aac(256)));
+        assert_eq!(CoverArtType::Other("Booklet".to_owned()).picture_type(), 0);
+    }
+}
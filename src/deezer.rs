@@ -0,0 +1,663 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::sync::Arc;
+
+use blowfish::{
+    Blowfish,
+    cipher::{BlockDecryptMut, KeyIvInit, block_padding::NoPadding},
+};
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use md5::{Digest, Md5};
+use reqwest::{Client, RequestBuilder, Response, header};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use tokio::sync::RwLock;
+
+use crate::{
+    AudioFormat, AudioStream, Error, SearchResults, Track,
+    id::DeezerTrackId,
+    retry::{RetryPolicy, send_with_retry},
+    util::{check_status, paginate},
+};
+
+const API_URL: &str = "https://api.deezer.com";
+const GATEWAY_URL: &str = "https://www.deezer.com/ajax/gw-light.php";
+const MEDIA_URL: &str = "https://media.deezer.com/v1/get_url";
+const SEARCH_PAGE_SIZE: usize = 20;
+
+// Every third 2048 byte chunk of a stream is Blowfish CBC encrypted, a
+// trailing partial chunk never is
+const CHUNK_SIZE: usize = 2048;
+const STRIPE_SECRET: &[u8; 16] = b"g4el58wc0zvf9na1";
+const STRIPE_IV: [u8; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+
+type BlowfishCbcDec = cbc::Decryptor<Blowfish>;
+
+/// Formats requested from the media server, best first
+const FORMATS: &[(&str, AudioFormat)] = &[
+    ("FLAC", AudioFormat::Flac),
+    ("MP3_320", AudioFormat::Mp3(320)),
+    ("MP3_128", AudioFormat::Mp3(128)),
+];
+
+#[derive(Clone)]
+pub struct Deezer {
+    client: reqwest::Client,
+    arl: String,
+    // Gateway tokens are fetched with the `arl` cookie on first use and
+    // cleared once the gateway rejects them
+    session: Arc<RwLock<Option<Session>>>,
+    retry: RetryPolicy,
+}
+
+#[derive(Clone)]
+struct Session {
+    api_token: String,
+    license_token: String,
+    sid: Option<String>,
+}
+
+impl Deezer {
+    /// `arl` is the value of the `arl` cookie of a logged in deezer.com session
+    pub fn new(arl: String) -> Self {
+        Self {
+            client: Client::builder()
+                .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/122.0.0.0 Safari/537.36")
+                .build()
+                .unwrap(),
+            arl,
+            session: Arc::new(RwLock::new(None)),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Retries timeouts and server errors according to `retry`
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn service_name(&self) -> &'static str {
+        "deezer"
+    }
+
+    pub fn supported_formats(&self) -> Vec<AudioFormat> {
+        FORMATS.iter().map(|(_, format)| *format).collect()
+    }
+
+    fn gateway(&self, method: &str, session: Option<&Session>) -> RequestBuilder {
+        let mut cookie = format!("arl={}", self.arl);
+        if let Some(sid) = session.and_then(|s| s.sid.as_ref()) {
+            cookie += &format!("; sid={}", sid);
+        }
+        self.client
+            .post(GATEWAY_URL)
+            .query(&[
+                ("method", method),
+                ("input", "3"),
+                ("api_version", "1.0"),
+                ("api_token", session.map_or("", |s| s.api_token.as_str())),
+            ])
+            .header(header::COOKIE, cookie)
+    }
+
+    async fn send_gateway<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, Error> {
+        check_status(
+            send_with_retry(&self.retry, request).await?,
+            self.service_name(),
+        )?
+        .json::<data::GatewayResponse<T>>()
+        .await?
+        .into()
+    }
+
+    async fn session(&self) -> Result<Session, Error> {
+        if let Some(session) = self.session.read().await.as_ref() {
+            return Ok(session.clone());
+        }
+        // Concurrent callers wait here instead of fetching their own session
+        let mut session = self.session.write().await;
+        if let Some(session) = session.as_ref() {
+            return Ok(session.clone());
+        }
+        Ok(session.insert(self.fetch_session().await?).clone())
+    }
+
+    async fn fetch_session(&self) -> Result<Session, Error> {
+        let response = check_status(
+            send_with_retry(
+                &self.retry,
+                self.gateway("deezer.getUserData", None).json(&json!({})),
+            )
+            .await?,
+            self.service_name(),
+        )?;
+        // The API token is only valid together with the session cookie
+        let sid = response
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .find_map(|v| v.strip_prefix("sid="))
+            .and_then(|v| v.split(';').next())
+            .map(str::to_owned);
+        let user_data = Result::<data::UserData, Error>::from(
+            response
+                .json::<data::GatewayResponse<data::UserData>>()
+                .await?,
+        )?;
+        if user_data.user.user_id == 0 {
+            return Err(Error::AuthenticationError("invalid arl".to_owned()));
+        }
+        Ok(Session {
+            api_token: user_data.check_form,
+            license_token: user_data.user.options.license_token,
+            sid,
+        })
+    }
+
+    /// Sends `song.getData`, an expired API token is fetched again and the
+    /// request repeated once
+    async fn song_data(&self, id: &DeezerTrackId) -> Result<(Session, data::SongData), Error> {
+        let mut expired = false;
+        loop {
+            let session = self.session().await?;
+            let result = self
+                .send_gateway::<data::SongData>(
+                    self.gateway("song.getData", Some(&session))
+                        .json(&json!({ "sng_id": id.as_str() })),
+                )
+                .await;
+            match result {
+                Err(Error::AuthenticationError(_)) if !expired => {
+                    *self.session.write().await = None;
+                    expired = true;
+                }
+                result => return Ok((session, result?)),
+            }
+        }
+    }
+
+    pub async fn search(&self, query: &str, page: usize) -> Result<SearchResults, Error> {
+        let response = check_status(
+            send_with_retry(
+                &self.retry,
+                self.client.get(format!("{}/search", API_URL)).query(&[
+                    ("q", query),
+                    ("index", &(page * SEARCH_PAGE_SIZE).to_string()),
+                    ("limit", &SEARCH_PAGE_SIZE.to_string()),
+                ]),
+            )
+            .await?,
+            self.service_name(),
+        )?;
+        Result::<data::SearchResponse, Error>::from(
+            response
+                .json::<data::ApiResponse<data::SearchResponse>>()
+                .await?,
+        )
+        .map(SearchResults::from)
+    }
+
+    /// Fetches search pages on demand and yields their tracks one by one
+    pub fn search_all<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> impl Stream<Item = Result<Track, Error>> + 'a {
+        paginate(move |page| self.search(query, page))
+    }
+
+    /// Streams the best available format. Deezer serves the files encrypted,
+    /// the returned stream decrypts them on the fly.
+    pub async fn get_stream(&self, url: &str) -> Result<AudioStream, Error> {
+        let id = DeezerTrackId::try_from(url)?;
+        let (session, song) = self.song_data(&id).await?;
+
+        let formats = FORMATS
+            .iter()
+            .map(|(name, _)| json!({ "cipher": "BF_CBC_STRIPE", "format": name }))
+            .collect::<Vec<_>>();
+        let media = check_status(
+            send_with_retry(
+                &self.retry,
+                self.client.post(MEDIA_URL).json(&json!({
+                    "license_token": session.license_token,
+                    "media": [{ "type": "FULL", "formats": formats }],
+                    "track_tokens": [song.track_token],
+                })),
+            )
+            .await?,
+            self.service_name(),
+        )?
+        .json::<data::MediaResponse>()
+        .await?
+        .into_media()?;
+        let format = FORMATS
+            .iter()
+            .find(|(name, _)| *name == media.format)
+            .map(|(_, format)| *format)
+            .ok_or(Error::UnsupportedFormatError)?;
+        let source = media
+            .sources
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::ServiceError("no media sources".to_owned()))?;
+
+        let response = check_status(
+            send_with_retry(&self.retry, self.client.get(source.url)).await?,
+            self.service_name(),
+        )?;
+        Ok(AudioStream {
+            response: decrypt_response(response, track_key(id.as_str())),
+            format,
+            md5: None,
+        })
+    }
+}
+
+fn track_key(track_id: &str) -> [u8; 16] {
+    let hash = format!("{:x}", Md5::digest(track_id.as_bytes()));
+    let hash = hash.as_bytes();
+    std::array::from_fn(|i| hash[i] ^ hash[i + 16] ^ STRIPE_SECRET[i])
+}
+
+struct StripeDecryptor {
+    key: [u8; 16],
+    buffer: Vec<u8>,
+    chunk_index: usize,
+}
+
+impl StripeDecryptor {
+    fn new(key: [u8; 16]) -> Self {
+        Self {
+            key,
+            buffer: vec![],
+            chunk_index: 0,
+        }
+    }
+
+    /// Returns the data of every chunk completed by `data`
+    fn push(&mut self, data: &[u8]) -> Vec<u8> {
+        self.buffer.extend_from_slice(data);
+        let complete = self.buffer.len() / CHUNK_SIZE * CHUNK_SIZE;
+        let mut output = self.buffer.drain(..complete).collect::<Vec<_>>();
+        for chunk in output.chunks_exact_mut(CHUNK_SIZE) {
+            if self.chunk_index.is_multiple_of(3) {
+                // Neither can fail, the key, IV and chunk sizes are fixed
+                BlowfishCbcDec::new_from_slices(&self.key, &STRIPE_IV)
+                    .unwrap()
+                    .decrypt_padded_mut::<NoPadding>(chunk)
+                    .unwrap();
+            }
+            self.chunk_index += 1;
+        }
+        output
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+// Decryption keeps the length, so the original headers stay valid
+fn decrypt_response(response: Response, key: [u8; 16]) -> Response {
+    let mut builder = http::Response::builder().status(response.status());
+    if let Some(headers) = builder.headers_mut() {
+        *headers = response.headers().clone();
+    }
+    let body = futures::stream::unfold(
+        (
+            Box::pin(response.bytes_stream()),
+            Some(StripeDecryptor::new(key)),
+        ),
+        |(mut body, decryptor)| async move {
+            let mut decryptor = decryptor?;
+            match body.next().await {
+                Some(Ok(data)) => {
+                    let data = Bytes::from(decryptor.push(&data));
+                    Some((Ok(data), (body, Some(decryptor))))
+                }
+                Some(Err(e)) => Some((Err(e), (body, None))),
+                None => Some((Ok(Bytes::from(decryptor.finish())), (body, None))),
+            }
+        },
+    );
+    builder
+        .body(reqwest::Body::wrap_stream(body))
+        .unwrap()
+        .into()
+}
+
+mod data {
+    use std::marker::PhantomData;
+
+    use serde::{Deserialize, Deserializer, de::DeserializeOwned};
+    use serde_json::Value;
+
+    use crate::{Error, SearchResults};
+
+    #[derive(Debug, Deserialize)]
+    #[serde(untagged)]
+    pub enum ApiResponse<T> {
+        Err { error: ApiError },
+        Ok(T),
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct ApiError {
+        #[serde(rename = "type")]
+        pub error_type: String,
+        pub message: String,
+        pub code: Option<u32>,
+    }
+
+    impl<T> From<ApiResponse<T>> for Result<T, Error> {
+        fn from(value: ApiResponse<T>) -> Self {
+            match value {
+                ApiResponse::Ok(value) => Ok(value),
+                ApiResponse::Err { error } => {
+                    let message = format!("{}: {}", error.error_type, error.message);
+                    Err(match error.code {
+                        Some(4) => Error::RateLimitError(message),
+                        Some(300) => Error::AuthenticationError(message),
+                        _ => Error::ServiceError(message),
+                    })
+                }
+            }
+        }
+    }
+
+    // `error` is an empty array on success and an object keyed by error
+    // type otherwise, `results` is an empty object then
+    #[derive(Debug, Deserialize)]
+    pub struct GatewayResponse<T> {
+        pub error: Value,
+        pub results: Value,
+        #[serde(skip)]
+        pub marker: PhantomData<T>,
+    }
+
+    impl<T: DeserializeOwned> From<GatewayResponse<T>> for Result<T, Error> {
+        fn from(value: GatewayResponse<T>) -> Self {
+            match &value.error {
+                Value::Object(errors) if errors.contains_key("VALID_TOKEN_REQUIRED") => {
+                    Err(Error::AuthenticationError(value.error.to_string()))
+                }
+                Value::Object(errors) if !errors.is_empty() => {
+                    Err(Error::ServiceError(value.error.to_string()))
+                }
+                _ => Ok(serde_json::from_value(value.results)?),
+            }
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct UserData {
+        #[serde(rename = "checkForm")]
+        pub check_form: String,
+        #[serde(rename = "USER")]
+        pub user: User,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct User {
+        #[serde(rename = "USER_ID", deserialize_with = "number_or_string")]
+        pub user_id: u64,
+        #[serde(rename = "OPTIONS")]
+        pub options: UserOptions,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct UserOptions {
+        pub license_token: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct SongData {
+        #[serde(rename = "TRACK_TOKEN")]
+        pub track_token: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct MediaResponse {
+        pub data: Vec<MediaData>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct MediaData {
+        #[serde(default)]
+        pub media: Vec<Media>,
+        #[serde(default)]
+        pub errors: Vec<MediaError>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Media {
+        pub format: String,
+        pub sources: Vec<Source>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Source {
+        pub url: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct MediaError {
+        pub code: u32,
+        pub message: String,
+    }
+
+    impl MediaResponse {
+        pub fn into_media(self) -> Result<Media, Error> {
+            let data = self
+                .data
+                .into_iter()
+                .next()
+                .ok_or_else(|| Error::ServiceError("empty media response".to_owned()))?;
+            if let Some(error) = data.errors.into_iter().next() {
+                // 2002: the track token has no rights on any requested format
+                return Err(match error.code {
+                    2002 => Error::PaymentRequiredError(error.message),
+                    _ => Error::ServiceError(error.message),
+                });
+            }
+            data.media
+                .into_iter()
+                .next()
+                .ok_or_else(|| Error::ServiceError("track has no media".to_owned()))
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct SearchResponse {
+        pub data: Vec<Track>,
+        pub total: Option<usize>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Track {
+        pub id: u64,
+        pub title: String,
+        pub link: String,
+        pub duration: usize,
+        pub isrc: Option<String>,
+        pub md5_image: Option<String>,
+        pub artist: Artist,
+        pub album: Album,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Artist {
+        pub id: u64,
+        pub name: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Album {
+        pub title: String,
+    }
+
+    fn number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::Number(n) => n.as_u64(),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| serde::de::Error::custom("expected a numeric id"))
+    }
+
+    impl From<SearchResponse> for SearchResults {
+        fn from(value: SearchResponse) -> Self {
+            Self {
+                tracks: value.data.into_iter().map(Track::into).collect(),
+                total: value.total,
+                cache_hit: false,
+            }
+        }
+    }
+
+    impl From<Track> for crate::Track {
+        fn from(value: Track) -> Self {
+            let cover_uri_template = value.md5_image.filter(|m| !m.is_empty()).map(|md5| {
+                format!(
+                    "https://e-cdns-images.dzcdn.net/images/cover/{}/%%-000000-80-0-0.jpg",
+                    md5
+                )
+            });
+            Self {
+                id: value.id.to_string(),
+                url: value.link,
+                title: value.title,
+                duration_ms: value.duration * 1000,
+                artists: vec![crate::Artist {
+                    id: value.artist.id.to_string(),
+                    name: value.artist.name,
+                }],
+                cover_url: cover_uri_template
+                    .as_ref()
+                    .map(|t| t.replace("%%", "1000x1000"))
+                    .unwrap_or_default(),
+                cover_uri_template,
+                isrc: value.isrc,
+                album_label_id: None,
+                album: Some(value.album.title),
+                track_number: None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use blowfish::{
+        Blowfish,
+        cipher::{BlockEncryptMut, KeyIvInit, block_padding::NoPadding},
+    };
+
+    use crate::{
+        SearchResults,
+        deezer::{CHUNK_SIZE, STRIPE_IV, data, decrypt_response, track_key},
+        error::Error,
+    };
+
+    #[tokio::test]
+    async fn decrypt_stripes() {
+        let key = track_key("3135556");
+        let plain = (0..CHUNK_SIZE * 4 + 100)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+        let mut encrypted = plain.clone();
+        for (index, chunk) in encrypted.chunks_exact_mut(CHUNK_SIZE).enumerate() {
+            if index.is_multiple_of(3) {
+                cbc::Encryptor::<Blowfish>::new_from_slices(&key, &STRIPE_IV)
+                    .unwrap()
+                    .encrypt_padded_mut::<NoPadding>(chunk, CHUNK_SIZE)
+                    .unwrap();
+            }
+        }
+        assert_ne!(encrypted[..CHUNK_SIZE], plain[..CHUNK_SIZE]);
+        assert_eq!(
+            encrypted[CHUNK_SIZE..CHUNK_SIZE * 3],
+            plain[CHUNK_SIZE..CHUNK_SIZE * 3]
+        );
+
+        // Network chunks do not line up with cipher chunks
+        let body = reqwest::Body::wrap_stream(futures::stream::iter(
+            encrypted
+                .chunks(1000)
+                .map(|c| Ok::<_, std::io::Error>(c.to_vec()))
+                .collect::<Vec<_>>(),
+        ));
+        let response = decrypt_response(http::Response::new(body).into(), key);
+        assert_eq!(response.bytes().await.unwrap(), plain);
+    }
+
+    #[test]
+    fn search_response() {
+        let response: data::ApiResponse<data::SearchResponse> = serde_json::from_str(
+            r#"{"data": [{
+                "id": 3135556,
+                "readable": true,
+                "title": "Harder, Better, Faster, Stronger",
+                "link": "https://www.deezer.com/track/3135556",
+                "duration": 224,
+                "md5_image": "2e018122cb56986277102d2041a592c8",
+                "artist": {"id": 27, "name": "Daft Punk"},
+                "album": {"id": 302127, "title": "Discovery"}
+            }], "total": 1}"#,
+        )
+        .unwrap();
+        let results = SearchResults::from(Result::<_, Error>::from(response).unwrap());
+        let track = &results[0];
+        assert_eq!(track.duration_ms, 224_000);
+        assert_eq!(track.artists[0].name, "Daft Punk");
+        assert_eq!(
+            track.cover_url_at_size(500, 500).unwrap(),
+            "https://e-cdns-images.dzcdn.net/images/cover/2e018122cb56986277102d2041a592c8/500x500-000000-80-0-0.jpg"
+        );
+
+        let response: data::ApiResponse<data::SearchResponse> = serde_json::from_str(
+            r#"{"error": {"type": "Exception", "message": "Quota limit exceeded", "code": 4}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            Result::<_, Error>::from(response),
+            Err(Error::RateLimitError(_))
+        ));
+    }
+
+    #[test]
+    fn gateway_response() {
+        let response: data::GatewayResponse<data::UserData> = serde_json::from_str(
+            r#"{"error": [], "results": {
+                "checkForm": "token",
+                "USER": {"USER_ID": "42", "OPTIONS": {"license_token": "license"}}
+            }}"#,
+        )
+        .unwrap();
+        let user_data = Result::<_, Error>::from(response).unwrap();
+        assert_eq!(user_data.user.user_id, 42);
+        assert_eq!(user_data.user.options.license_token, "license");
+
+        let response: data::GatewayResponse<data::SongData> = serde_json::from_str(
+            r#"{"error": {"VALID_TOKEN_REQUIRED": "Invalid CSRF token"}, "results": {}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            Result::<_, Error>::from(response),
+            Err(Error::AuthenticationError(_))
+        ));
+
+        let response: data::MediaResponse = serde_json::from_str(
+            r#"{"data": [{"errors": [{"code": 2002, "message": "no sufficient rights"}]}]}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            response.into_media(),
+            Err(Error::PaymentRequiredError(_))
+        ));
+    }
+}
@@ -1,8 +1,12 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
+use std::path::PathBuf;
+
 use thiserror::Error;
 
+use crate::format::CoverFormat;
+
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum Error {
@@ -13,22 +17,210 @@ pub enum Error {
     #[error("unsupported format")]
     UnsupportedFormatError,
 
+    #[error("authentication error: {0}")]
+    AuthenticationError(String),
+
+    #[error("payment required: {0}")]
+    PaymentRequiredError(String),
+
+    #[error("rate limited: {0}")]
+    RateLimitError(String),
+
+    #[error("integrity check failed for {path}: expected {expected}, got {actual}")]
+    IntegrityError {
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("size {actual} exceeds limit of {limit} bytes")]
+    SizeExceeded { actual: u64, limit: u64 },
+
+    #[error("invalid id: {0}")]
+    InvalidIdError(String),
+
+    #[error("lrc parse error: {0}")]
+    LrcParseError(String),
+
+    #[error("playlist parse error: {0}")]
+    PlaylistParseError(String),
+
+    #[error("invalid metadata key: '{0}'")]
+    InvalidMetadataKey(String),
+
+    #[error("template error: {0}")]
+    TemplateError(String),
+
+    #[error(
+        "cover format mismatch: detected {detected:?}, file extension suggests {file_extension_suggested:?}"
+    )]
+    CoverFormatMismatch {
+        detected: CoverFormat,
+        file_extension_suggested: CoverFormat,
+    },
+
+    #[error("{context}: {source}")]
+    ContextError { context: String, source: Box<Error> },
+
     // Foreign errors
-    #[error(transparent)]
+    #[error("io error: {0}")]
     Io(#[from] std::io::Error),
 
-    #[error(transparent)]
+    #[error("remux error: {0}")]
     RemuxError(#[from] ffmpeg_next::Error),
 
-    #[error(transparent)]
+    #[error("json deserialization error: {0}")]
     JsonDeserializationError(#[from] serde_json::Error),
 
-    #[error(transparent)]
+    #[error("request error: {0}")]
     RequestError(#[from] reqwest::Error),
 
-    #[error(transparent)]
-    UrlParseError(#[from] url::ParseError),
+    #[error("failed to parse URL '{url}': {source}")]
+    UrlParseError {
+        url: String,
+        source: url::ParseError,
+    },
 
-    #[error(transparent)]
+    #[error("environment variable error: {0}")]
     EnvError(#[from] std::env::VarError),
+
+    #[error("xml error: {0}")]
+    XmlError(#[from] quick_xml::Error),
+
+    #[error("image error: {0}")]
+    ImageError(#[from] image::ImageError),
+}
+
+impl Error {
+    pub fn context(self, context: impl Into<String>) -> Error {
+        Error::ContextError {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// The request never got a response, e.g. no connection, DNS failure or
+    /// timeout. HTTP error statuses are not network errors.
+    pub fn is_network_error(&self) -> bool {
+        match self {
+            Self::RequestError(e) => {
+                !e.is_status() && (e.is_connect() || e.is_timeout() || e.is_request())
+            }
+            Self::ContextError { source, .. } => source.is_network_error(),
+            _ => false,
+        }
+    }
+
+    pub fn is_server_error(&self) -> bool {
+        match self {
+            Self::ServiceError(_) | Self::RemuxError(_) => true,
+            Self::ContextError { source, .. } => source.is_server_error(),
+            _ => false,
+        }
+    }
+
+    /// Retrying will not help until the credentials or subscription change
+    pub fn is_client_error(&self) -> bool {
+        match self {
+            Self::AuthenticationError(_) | Self::PaymentRequiredError(_) => true,
+            Self::ContextError { source, .. } => source.is_client_error(),
+            _ => false,
+        }
+    }
+}
+
+pub trait ResultExt<T> {
+    fn context(self, context: impl Into<String>) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Error>> ResultExt<T> for Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T, Error> {
+        self.map_err(|e| e.into().context(context))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        error::{Error, ResultExt},
+        util::join_url,
+    };
+    use std::error::Error as _;
+
+    #[test]
+    fn context() {
+        let error = Err::<(), _>(Error::UnsupportedFormatError)
+            .context("while processing /tmp/foo.flac")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "while processing /tmp/foo.flac: unsupported format"
+        );
+        assert_eq!(error.source().unwrap().to_string(), "unsupported format");
+        assert!(matches!(
+            error,
+            Error::ContextError { source, .. } if matches!(*source, Error::UnsupportedFormatError)
+        ));
+    }
+
+    #[test]
+    fn url_parse_error() {
+        assert_eq!(
+            join_url("music.example", "/search")
+                .unwrap_err()
+                .to_string(),
+            "failed to parse URL 'music.example': relative URL without a base"
+        );
+    }
+
+    #[test]
+    fn source() {
+        fn source_is<T: std::error::Error + 'static>(error: impl Into<Error>) -> bool {
+            error.into().source().is_some_and(|source| source.is::<T>())
+        }
+
+        assert!(source_is::<std::io::Error>(std::io::Error::other("io")));
+        assert!(source_is::<ffmpeg_next::Error>(
+            ffmpeg_next::Error::StreamNotFound
+        ));
+        assert!(source_is::<serde_json::Error>(
+            serde_json::from_str::<u8>("json").unwrap_err()
+        ));
+        assert!(source_is::<reqwest::Error>(
+            reqwest::Client::new().get("::").build().unwrap_err()
+        ));
+        assert!(source_is::<url::ParseError>(Error::UrlParseError {
+            url: "url".to_owned(),
+            source: url::Url::parse("url").unwrap_err()
+        }));
+        assert!(source_is::<std::env::VarError>(
+            std::env::VarError::NotPresent
+        ));
+        assert!(Error::UnsupportedFormatError.source().is_none());
+    }
+
+    #[tokio::test]
+    async fn classification() {
+        // Nothing listens on the port once the listener is dropped
+        let address = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let error = Error::from(
+            reqwest::get(format!("http://{}", address))
+                .await
+                .unwrap_err(),
+        )
+        .context("while searching");
+        assert!(error.is_network_error());
+        assert!(!error.is_server_error());
+        assert!(!error.is_client_error());
+
+        assert!(Error::ServiceError("internal error".to_owned()).is_server_error());
+        assert!(Error::RemuxError(ffmpeg_next::Error::StreamNotFound).is_server_error());
+        assert!(Error::AuthenticationError("bad token".to_owned()).is_client_error());
+        assert!(Error::PaymentRequiredError("subscription".to_owned()).is_client_error());
+        assert!(!Error::ServiceError("internal error".to_owned()).is_network_error());
+        assert!(!Error::RateLimitError("slow down".to_owned()).is_client_error());
+    }
 }
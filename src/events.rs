@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::{
+    path::{Path, PathBuf},
+    pin::pin,
+};
+
+use futures::TryStreamExt;
+use tokio::sync::broadcast;
+
+use crate::{
+    AudioStream,
+    error::Error,
+    format::{AudioFormat, Format},
+    save_audio_stream_streaming,
+};
+
+const DEFAULT_CAPACITY: usize = 256;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DownloadEvent {
+    Started {
+        track_id: String,
+        service: String,
+    },
+    Progress {
+        track_id: String,
+        bytes: u64,
+        total: Option<u64>,
+    },
+    Completed {
+        track_id: String,
+        path: PathBuf,
+        format: AudioFormat,
+    },
+    Failed {
+        track_id: String,
+        error: String,
+    },
+}
+
+/// Broadcasts download lifecycle events to every subscriber. Events sent
+/// while nobody is subscribed are dropped, slow subscribers lag behind and
+/// lose the oldest events once `capacity` is exceeded.
+pub struct EventBus {
+    sender: broadcast::Sender<DownloadEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sender: broadcast::channel(capacity).0,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DownloadEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn emit(&self, event: DownloadEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Same as [`crate::save_audio_stream`], emitting events along the way
+    pub async fn save_audio_stream(
+        &self,
+        track_id: &str,
+        service: &str,
+        audio_stream: AudioStream,
+        dir: &Path,
+        filename: &str,
+    ) -> Result<PathBuf, Error> {
+        self.emit(DownloadEvent::Started {
+            track_id: track_id.to_owned(),
+            service: service.to_owned(),
+        });
+        let format = audio_stream.format;
+        let path = dir.join(format!("{}.{}", filename, format.extension()));
+        let result = async {
+            let mut progress = pin!(save_audio_stream_streaming(audio_stream, &path));
+            while let Some(progress) = progress.try_next().await? {
+                self.emit(DownloadEvent::Progress {
+                    track_id: track_id.to_owned(),
+                    bytes: progress.bytes_downloaded,
+                    total: progress.total_bytes,
+                });
+            }
+            Ok::<_, Error>(())
+        }
+        .await;
+        match result {
+            Ok(()) => {
+                self.emit(DownloadEvent::Completed {
+                    track_id: track_id.to_owned(),
+                    path: path.clone(),
+                    format,
+                });
+                Ok(path)
+            }
+            Err(error) => {
+                self.emit(DownloadEvent::Failed {
+                    track_id: track_id.to_owned(),
+                    error: error.to_string(),
+                });
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AudioStream,
+        events::{DownloadEvent, EventBus},
+        format::AudioFormat,
+    };
+
+    #[tokio::test]
+    async fn save_audio_stream() {
+        let bus = EventBus::default();
+        let mut events = bus.subscribe();
+        let stream = AudioStream {
+            response: http::Response::new(reqwest::Body::from("fruityger!")).into(),
+            format: AudioFormat::Mp3(320),
+            md5: None,
+        };
+        let dir = std::env::temp_dir();
+        let path = bus
+            .save_audio_stream("1", "test", stream, &dir, "fruityger_events_test")
+            .await
+            .unwrap();
+
+        let expected = [
+            DownloadEvent::Started {
+                track_id: "1".to_owned(),
+                service: "test".to_owned(),
+            },
+            DownloadEvent::Progress {
+                track_id: "1".to_owned(),
+                bytes: 10,
+                total: Some(10),
+            },
+            DownloadEvent::Completed {
+                track_id: "1".to_owned(),
+                path: dir.join("fruityger_events_test.mp3"),
+                format: AudioFormat::Mp3(320),
+            },
+        ];
+        for event in expected {
+            assert_eq!(events.recv().await.unwrap(), event);
+        }
+        assert!(events.try_recv().is_err());
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"fruityger!");
+
+        let stream = AudioStream {
+            response: http::Response::new(reqwest::Body::from("")).into(),
+            format: AudioFormat::Flac,
+            md5: None,
+        };
+        let missing = dir.join("fruityger_events_test_missing");
+        assert!(
+            bus.save_audio_stream("2", "test", stream, &missing, "track")
+                .await
+                .is_err()
+        );
+        events.recv().await.unwrap();
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            DownloadEvent::Failed { track_id, .. } if track_id == "2"
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{MultiArtistSeparator, Track, error::Error};
+
+const COLUMNS: &[&str] = &[
+    "id",
+    "service",
+    "title",
+    "artist",
+    "album",
+    "track_number",
+    "disc_number",
+    "duration_ms",
+    "isrc",
+    "explicit",
+    "cover_url",
+    "url",
+];
+
+// Tracks do not carry the service, disc number or explicit flag yet, those
+// columns are kept so the layout stays stable once they do
+#[derive(Serialize)]
+struct Row<'a> {
+    id: &'a str,
+    service: Option<&'a str>,
+    title: &'a str,
+    artist: String,
+    album: Option<&'a str>,
+    track_number: Option<u32>,
+    disc_number: Option<u32>,
+    duration_ms: usize,
+    isrc: Option<&'a str>,
+    explicit: Option<bool>,
+    cover_url: &'a str,
+    url: &'a str,
+}
+
+impl<'a> From<&'a Track> for Row<'a> {
+    fn from(track: &'a Track) -> Self {
+        Self {
+            id: &track.id,
+            service: None,
+            title: &track.title,
+            artist: track.artist_names_joined(MultiArtistSeparator::default().as_str()),
+            album: track.album.as_deref(),
+            track_number: track.track_number,
+            disc_number: None,
+            duration_ms: track.duration_ms,
+            isrc: track.isrc.as_deref(),
+            explicit: None,
+            cover_url: &track.cover_url,
+            url: &track.url,
+        }
+    }
+}
+
+fn write_delimited(
+    tracks: &[Track],
+    path: &Path,
+    delimiter: &str,
+    line_ending: &str,
+    field: impl Fn(&Value) -> String,
+) -> Result<(), Error> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write!(writer, "{}{}", COLUMNS.join(delimiter), line_ending)?;
+    for track in tracks {
+        let row = serde_json::to_value(Row::from(track))?;
+        let fields = COLUMNS.iter().map(|c| field(&row[c])).collect::<Vec<_>>();
+        write!(writer, "{}{}", fields.join(delimiter), line_ending)?;
+    }
+    Ok(writer.flush()?)
+}
+
+/// Writes an RFC 4180 CSV with one row per track, string fields are always
+/// quoted and lines end with CRLF
+pub fn export_to_csv(tracks: &[Track], path: &Path) -> Result<(), Error> {
+    write_delimited(tracks, path, ",", "\r\n", |value| match value {
+        Value::Null => String::new(),
+        Value::String(s) => format!("\"{}\"", s.replace('"', "\"\"")),
+        value => value.to_string(),
+    })
+}
+
+/// Same columns as [`export_to_csv`], tabs and line breaks inside values
+/// are replaced with spaces since TSV has no quoting
+pub fn export_to_tsv(tracks: &[Track], path: &Path) -> Result<(), Error> {
+    write_delimited(tracks, path, "\t", "\n", |value| match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.replace(['\t', '\r', '\n'], " "),
+        value => value.to_string(),
+    })
+}
+
+/// Writes one JSON object per track and line (NDJSON)
+pub fn export_to_json_lines(tracks: &[Track], path: &Path) -> Result<(), Error> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for track in tracks {
+        serde_json::to_writer(&mut writer, &Row::from(track))?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(writer.flush()?)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::{
+        Track,
+        export::{export_to_csv, export_to_json_lines, export_to_tsv},
+    };
+
+    #[test]
+    fn export() {
+        let tracks = [
+            Track::example(),
+            Track {
+                title: "Hello, \"World\"\tAgain".to_owned(),
+                album: None,
+                track_number: None,
+                ..Track::example()
+            },
+        ];
+        let dir = std::env::temp_dir();
+
+        let path = dir.join("fruityger_export_test.csv");
+        export_to_csv(&tracks, &path).unwrap();
+        let csv = fs::read_to_string(&path).unwrap();
+        assert_eq!(csv.matches("\r\n").count(), 3);
+        assert!(csv.ends_with("\r\n"));
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            "id,service,title,artist,album,track_number,disc_number,duration_ms,isrc,explicit,cover_url,url"
+        );
+        assert_eq!(
+            lines[1],
+            "\"2000001\",,\"Test Track\",\"Test Artist\",\"Test Album\",1,,215000,\"GB-ABC-25-00001\",,\"https://example.com/cover/3000001.jpg\",\"https://example.com/track/2000001\""
+        );
+        assert!(lines[2].contains(",\"Hello, \"\"World\"\"\tAgain\",\"Test Artist\",,,,215000,"));
+
+        let path = dir.join("fruityger_export_test.tsv");
+        export_to_tsv(&tracks, &path).unwrap();
+        let tsv = fs::read_to_string(&path).unwrap();
+        assert!(!tsv.contains('\r'));
+        let lines = tsv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0].split('\t').count(), 12);
+        assert_eq!(lines[2].split('\t').nth(2), Some("Hello, \"World\" Again"));
+
+        let path = dir.join("fruityger_export_test.ndjson");
+        export_to_json_lines(&tracks, &path).unwrap();
+        let ndjson = fs::read_to_string(&path).unwrap();
+        let rows = ndjson
+            .lines()
+            .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["artist"], "Test Artist");
+        assert_eq!(rows[1]["album"], serde_json::Value::Null);
+    }
+}
@@ -1,35 +1,207 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
+use std::{fmt, path::Path, str::FromStr};
+
+use ffmpeg_next::{codec, media};
+use serde::{Deserialize, Serialize};
+
 use crate::error::Error;
 
+fn has_extension(path: &str, extension: &str) -> bool {
+    path.strip_suffix(extension)
+        .is_some_and(|path| path.ends_with('.'))
+}
+
 pub trait Format {
     fn extension(&self) -> &'static str;
 
     fn mime_type(&self) -> &'static str;
 }
 
-#[derive(Debug, Clone)]
+/// Serialized as `{"type":"mp3","bitrate":320}`, the type names match the
+/// [`Display`](fmt::Display) form
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "bitrate", rename_all = "kebab-case")]
 pub enum AudioFormat {
+    #[default]
     Flac,
+    FlacMp4,
     Mp3(u16),
     Aac(u16),
+    /// Apple Lossless, shares the M4A container with AAC
+    Alac,
+    Opus(u16),
+    Vorbis(u16),
+    /// Uncompressed PCM, neither container can hold cover art
+    Wav,
+    Aiff,
 }
 
 impl Format for AudioFormat {
     fn extension(&self) -> &'static str {
+        match self {
+            Self::Flac => Self::FLAC_EXTENSION,
+            Self::Mp3(_) => Self::MP3_EXTENSION,
+            Self::FlacMp4 | Self::Aac(_) | Self::Alac => Self::M4A_EXTENSION,
+            Self::Opus(_) => Self::OPUS_EXTENSION,
+            Self::Vorbis(_) => Self::OGG_EXTENSION,
+            Self::Wav => Self::WAV_EXTENSION,
+            Self::Aiff => Self::AIFF_EXTENSION,
+        }
+    }
+
+    fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Flac => Self::FLAC_MIME_TYPE,
+            Self::Mp3(_) => Self::MP3_MIME_TYPE,
+            Self::FlacMp4 | Self::Aac(_) | Self::Alac => Self::MP4_MIME_TYPE,
+            Self::Opus(_) | Self::Vorbis(_) => Self::OGG_MIME_TYPE,
+            Self::Wav => Self::WAV_MIME_TYPE,
+            Self::Aiff => Self::AIFF_MIME_TYPE,
+        }
+    }
+}
+
+impl AudioFormat {
+    pub const FLAC_MIME_TYPE: &str = "audio/flac";
+    pub const MP3_MIME_TYPE: &str = "audio/mpeg";
+    /// Shared by every format in the MP4 container
+    pub const MP4_MIME_TYPE: &str = "audio/mp4";
+    /// Shared by every format in the Ogg container
+    pub const OGG_MIME_TYPE: &str = "audio/ogg";
+    pub const OPUS_MIME_TYPE: &str = "audio/opus";
+    pub const WAV_MIME_TYPE: &str = "audio/wav";
+    pub const AIFF_MIME_TYPE: &str = "audio/aiff";
+
+    pub const FLAC_EXTENSION: &str = "flac";
+    pub const MP3_EXTENSION: &str = "mp3";
+    pub const M4A_EXTENSION: &str = "m4a";
+    pub const OPUS_EXTENSION: &str = "opus";
+    pub const OGG_EXTENSION: &str = "ogg";
+    pub const WAV_EXTENSION: &str = "wav";
+    pub const AIFF_EXTENSION: &str = "aiff";
+
+    pub fn is_lossless(&self) -> bool {
+        matches!(
+            self,
+            Self::Flac | Self::FlacMp4 | Self::Alac | Self::Wav | Self::Aiff
+        )
+    }
+
+    /// Higher is better, lossless formats rank above any lossy bitrate
+    pub fn quality_rank(&self) -> u32 {
+        match self {
+            Self::Flac | Self::FlacMp4 | Self::Alac | Self::Wav | Self::Aiff => {
+                u32::from(u16::MAX) + 1
+            }
+            Self::Mp3(bitrate)
+            | Self::Aac(bitrate)
+            | Self::Opus(bitrate)
+            | Self::Vorbis(bitrate) => u32::from(*bitrate),
+        }
+    }
+
+    /// Lossy formats use a bitrate of 0 when the service did not report it,
+    /// lossless formats carry no bitrate at all
+    pub fn is_bitrate_known(&self) -> bool {
+        match self {
+            Self::Mp3(bitrate)
+            | Self::Aac(bitrate)
+            | Self::Opus(bitrate)
+            | Self::Vorbis(bitrate) => *bitrate != 0,
+            Self::Flac | Self::FlacMp4 | Self::Alac | Self::Wav | Self::Aiff => false,
+        }
+    }
+
+    pub(crate) fn muxer_name(&self) -> &'static str {
         match self {
             Self::Flac => "flac",
+            // The ipod muxer picked for .m4a has no FLAC codec tag
+            Self::FlacMp4 => "mp4",
             Self::Mp3(_) => "mp3",
-            Self::Aac(_) => "m4a",
+            Self::Aac(_) | Self::Alac => "ipod",
+            Self::Opus(_) | Self::Vorbis(_) => "ogg",
+            Self::Wav => "wav",
+            Self::Aiff => "aiff",
         }
     }
 
-    fn mime_type(&self) -> &'static str {
+    /// Detects the format from the codec of the best audio stream, which is
+    /// the only way to tell ALAC, AAC and FLAC in M4A files apart
+    pub fn from_probe(path: &Path) -> Result<Self, Error> {
+        let input = ffmpeg_next::format::input(&path)?;
+        let stream = input
+            .streams()
+            .best(media::Type::Audio)
+            .ok_or(ffmpeg_next::Error::StreamNotFound)?;
+        let parameters = stream.parameters();
+        let bitrate = unsafe { (*parameters.as_ptr()).bit_rate } / 1000;
+        let bitrate = u16::try_from(bitrate).unwrap_or(0);
+        // The MP4 demuxer is registered as "mov,mp4,m4a,3gp,3g2,mj2"
+        let format = input.format();
+        let name = format.name();
+        let is_mp4 = name.split(',').any(|n| n == "mp4");
+        match parameters.id() {
+            // PCM comes in too many sample formats to match on the codec
+            _ if name == "wav" => Ok(Self::Wav),
+            _ if name == "aiff" => Ok(Self::Aiff),
+            codec::Id::FLAC if is_mp4 => Ok(Self::FlacMp4),
+            codec::Id::FLAC => Ok(Self::Flac),
+            codec::Id::ALAC => Ok(Self::Alac),
+            codec::Id::MP3 => Ok(Self::Mp3(bitrate)),
+            codec::Id::AAC => Ok(Self::Aac(bitrate)),
+            codec::Id::OPUS => Ok(Self::Opus(bitrate)),
+            codec::Id::VORBIS => Ok(Self::Vorbis(bitrate)),
+            _ => Err(Error::UnsupportedFormatError),
+        }
+    }
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Flac => "audio/flac",
-            Self::Mp3(_) => "audio/mpeg",
-            Self::Aac(_) => "audio/mp4",
+            Self::Flac => write!(f, "flac"),
+            Self::FlacMp4 => write!(f, "flac-mp4"),
+            Self::Mp3(0) => write!(f, "mp3"),
+            Self::Mp3(bitrate) => write!(f, "mp3:{}", bitrate),
+            Self::Aac(0) => write!(f, "aac"),
+            Self::Aac(bitrate) => write!(f, "aac:{}", bitrate),
+            Self::Alac => write!(f, "alac"),
+            Self::Opus(0) => write!(f, "opus"),
+            Self::Opus(bitrate) => write!(f, "opus:{}", bitrate),
+            Self::Vorbis(0) => write!(f, "vorbis"),
+            Self::Vorbis(bitrate) => write!(f, "vorbis:{}", bitrate),
+            Self::Wav => write!(f, "wav"),
+            Self::Aiff => write!(f, "aiff"),
+        }
+    }
+}
+
+/// Parses the [`Display`](fmt::Display) form, e.g. `flac` or `mp3:320`
+impl FromStr for AudioFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        let (name, bitrate) = match s.split_once(':') {
+            Some((name, bitrate)) => (
+                name,
+                Some(bitrate.parse().map_err(|_| Error::UnsupportedFormatError)?),
+            ),
+            None => (s.as_str(), None),
+        };
+        match (name, bitrate) {
+            ("flac", None) => Ok(Self::Flac),
+            ("flac-mp4", None) => Ok(Self::FlacMp4),
+            ("alac", None) => Ok(Self::Alac),
+            ("wav", None) => Ok(Self::Wav),
+            ("aiff", None) => Ok(Self::Aiff),
+            ("mp3", bitrate) => Ok(Self::Mp3(bitrate.unwrap_or(0))),
+            ("aac", bitrate) => Ok(Self::Aac(bitrate.unwrap_or(0))),
+            ("opus", bitrate) => Ok(Self::Opus(bitrate.unwrap_or(0))),
+            ("vorbis", bitrate) => Ok(Self::Vorbis(bitrate.unwrap_or(0))),
+            _ => Err(Error::UnsupportedFormatError),
         }
     }
 }
@@ -39,16 +211,33 @@ impl TryFrom<&str> for AudioFormat {
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
-            "audio/flac" | "audio/x-flac" => Ok(AudioFormat::Flac),
-            "audio/mpeg" | "audio/mpg" => Ok(AudioFormat::Mp3(0)),
-            "audio/mp4" | "video/mp4" | "audio/aac" => Ok(AudioFormat::Aac(0)),
+            Self::FLAC_MIME_TYPE | "audio/x-flac" => Ok(AudioFormat::Flac),
+            Self::MP3_MIME_TYPE | "audio/mpg" => Ok(AudioFormat::Mp3(0)),
+            Self::MP4_MIME_TYPE | "video/mp4" | "audio/aac" => Ok(AudioFormat::Aac(0)),
+            // Plain Ogg audio is traditionally Vorbis, Opus has its own type
+            Self::OGG_MIME_TYPE | "audio/vorbis" => Ok(AudioFormat::Vorbis(0)),
+            Self::OPUS_MIME_TYPE => Ok(AudioFormat::Opus(0)),
+            Self::WAV_MIME_TYPE | "audio/x-wav" | "audio/wave" => Ok(AudioFormat::Wav),
+            Self::AIFF_MIME_TYPE | "audio/x-aiff" => Ok(AudioFormat::Aiff),
+            _ if value.starts_with('{') => {
+                serde_json::from_str(value).map_err(|_| Error::UnsupportedFormatError)
+            }
             _ => {
-                if value.ends_with(".flac") {
+                if has_extension(value, Self::FLAC_EXTENSION) {
                     Ok(AudioFormat::Flac)
-                } else if value.ends_with(".mp3") {
+                } else if has_extension(value, Self::MP3_EXTENSION) {
                     Ok(AudioFormat::Mp3(0))
-                } else if value.ends_with(".m4a") || value.ends_with(".mp4") {
+                } else if has_extension(value, Self::M4A_EXTENSION) || has_extension(value, "mp4") {
                     Ok(AudioFormat::Aac(0))
+                } else if has_extension(value, Self::OPUS_EXTENSION) {
+                    Ok(AudioFormat::Opus(0))
+                } else if has_extension(value, Self::OGG_EXTENSION) {
+                    Ok(AudioFormat::Vorbis(0))
+                } else if has_extension(value, Self::WAV_EXTENSION) {
+                    Ok(AudioFormat::Wav)
+                } else if has_extension(value, Self::AIFF_EXTENSION) || has_extension(value, "aif")
+                {
+                    Ok(AudioFormat::Aiff)
                 } else {
                     Err(Error::UnsupportedFormatError)
                 }
@@ -57,24 +246,196 @@ impl TryFrom<&str> for AudioFormat {
     }
 }
 
-#[derive(Debug)]
+/// Best-effort guess from CDN path conventions such as `/flac/track.flac` or
+/// `/mp3-320/track`, for when the response carries no usable content type
+pub fn detect_format_from_url(url: &str) -> Option<AudioFormat> {
+    let path = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let segments = path.split('/').collect::<Vec<_>>();
+    for segment in &segments {
+        let bitrate = |prefix| {
+            segment
+                .strip_prefix(prefix)
+                .and_then(|b: &str| b.parse::<u16>().ok())
+        };
+        if let Some(bitrate) = bitrate("mp3-") {
+            return Some(AudioFormat::Mp3(bitrate));
+        } else if let Some(bitrate) = bitrate("aac-") {
+            return Some(AudioFormat::Aac(bitrate));
+        }
+    }
+    match segments.last()?.rsplit_once('.')?.1 {
+        "flac" => Some(AudioFormat::Flac),
+        "mp3" => Some(AudioFormat::Mp3(0)),
+        "m4a" | "mp4" => Some(AudioFormat::Aac(0)),
+        "opus" => Some(AudioFormat::Opus(0)),
+        "ogg" => Some(AudioFormat::Vorbis(0)),
+        "wav" => Some(AudioFormat::Wav),
+        "aiff" | "aif" => Some(AudioFormat::Aiff),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QobuzQualityId(pub u8);
+
+impl QobuzQualityId {
+    pub const MP3_320: Self = Self(5);
+    pub const FLAC_16: Self = Self(6);
+    pub const FLAC_24: Self = Self(7);
+    pub const FLAC_24_192: Self = Self(27);
+}
+
+impl TryFrom<QobuzQualityId> for AudioFormat {
+    type Error = Error;
+
+    fn try_from(value: QobuzQualityId) -> Result<Self, Self::Error> {
+        match value {
+            QobuzQualityId::MP3_320 => Ok(AudioFormat::Mp3(320)),
+            QobuzQualityId::FLAC_16 | QobuzQualityId::FLAC_24 | QobuzQualityId::FLAC_24_192 => {
+                Ok(AudioFormat::Flac)
+            }
+            _ => Err(Error::UnsupportedFormatError),
+        }
+    }
+}
+
+impl TryFrom<AudioFormat> for QobuzQualityId {
+    type Error = Error;
+
+    fn try_from(value: AudioFormat) -> Result<Self, Self::Error> {
+        match value {
+            AudioFormat::Flac => Ok(QobuzQualityId::FLAC_16),
+            AudioFormat::Mp3(_) => Ok(QobuzQualityId::MP3_320),
+            AudioFormat::FlacMp4
+            | AudioFormat::Aac(_)
+            | AudioFormat::Alac
+            | AudioFormat::Opus(_)
+            | AudioFormat::Vorbis(_)
+            | AudioFormat::Wav
+            | AudioFormat::Aiff => Err(Error::UnsupportedFormatError),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YandexQuality {
+    Low,
+    High,
+    Lossless,
+}
+
+impl YandexQuality {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "lq",
+            Self::High => "nq",
+            Self::Lossless => "lossless",
+        }
+    }
+}
+
+impl From<YandexQuality> for AudioFormat {
+    fn from(value: YandexQuality) -> Self {
+        match value {
+            YandexQuality::Low => AudioFormat::Mp3(128),
+            YandexQuality::High => AudioFormat::Mp3(320),
+            YandexQuality::Lossless => AudioFormat::FlacMp4,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HifiQuality {
+    Low,
+    High,
+    #[default]
+    Lossless,
+    HiRes,
+}
+
+impl HifiQuality {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "LOW",
+            Self::High => "HIGH",
+            Self::Lossless => "LOSSLESS",
+            Self::HiRes => "HI_RES",
+        }
+    }
+}
+
+impl From<HifiQuality> for AudioFormat {
+    fn from(value: HifiQuality) -> Self {
+        match value {
+            HifiQuality::Low => AudioFormat::Aac(96),
+            HifiQuality::High => AudioFormat::Aac(320),
+            HifiQuality::Lossless | HifiQuality::HiRes => AudioFormat::Flac,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
 pub enum CoverFormat {
     Png,
+    #[default]
     Jpeg,
+    WebP,
+    Avif,
 }
 
 impl Format for CoverFormat {
     fn extension(&self) -> &'static str {
         match self {
-            Self::Png => "png",
-            Self::Jpeg => "jpg",
+            Self::Png => Self::PNG_EXTENSION,
+            Self::Jpeg => Self::JPEG_EXTENSION,
+            Self::WebP => Self::WEBP_EXTENSION,
+            Self::Avif => Self::AVIF_EXTENSION,
         }
     }
 
     fn mime_type(&self) -> &'static str {
         match self {
-            Self::Png => "image/png",
-            Self::Jpeg => "image/jpeg",
+            Self::Png => Self::PNG_MIME_TYPE,
+            Self::Jpeg => Self::JPEG_MIME_TYPE,
+            Self::WebP => Self::WEBP_MIME_TYPE,
+            Self::Avif => Self::AVIF_MIME_TYPE,
+        }
+    }
+}
+
+impl CoverFormat {
+    pub const PNG_MIME_TYPE: &str = "image/png";
+    pub const JPEG_MIME_TYPE: &str = "image/jpeg";
+    pub const WEBP_MIME_TYPE: &str = "image/webp";
+    pub const AVIF_MIME_TYPE: &str = "image/avif";
+
+    pub const PNG_EXTENSION: &str = "png";
+    pub const JPEG_EXTENSION: &str = "jpg";
+    pub const WEBP_EXTENSION: &str = "webp";
+    pub const AVIF_EXTENSION: &str = "avif";
+
+    pub fn ffmpeg_codec_id(&self) -> codec::Id {
+        match self {
+            Self::Png => codec::Id::PNG,
+            Self::Jpeg => codec::Id::MJPEG,
+            Self::WebP => codec::Id::WEBP,
+            // AVIF images are single AV1 frames
+            Self::Avif => codec::Id::AV1,
+        }
+    }
+
+    pub fn from_ffmpeg_codec_id(id: codec::Id) -> Option<Self> {
+        match id {
+            codec::Id::PNG => Some(Self::Png),
+            codec::Id::MJPEG => Some(Self::Jpeg),
+            codec::Id::WEBP => Some(Self::WebP),
+            codec::Id::AV1 => Some(Self::Avif),
+            _ => None,
         }
     }
 }
@@ -84,13 +445,22 @@ impl TryFrom<&str> for CoverFormat {
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
-            "image/jpeg" => Ok(CoverFormat::Jpeg),
-            "image/png" => Ok(CoverFormat::Png),
+            Self::JPEG_MIME_TYPE => Ok(CoverFormat::Jpeg),
+            Self::PNG_MIME_TYPE => Ok(CoverFormat::Png),
+            Self::WEBP_MIME_TYPE => Ok(CoverFormat::WebP),
+            Self::AVIF_MIME_TYPE => Ok(CoverFormat::Avif),
+            _ if value.starts_with('{') => {
+                serde_json::from_str(value).map_err(|_| Error::UnsupportedFormatError)
+            }
             _ => {
-                if value.ends_with(".jpg") {
+                if has_extension(value, Self::JPEG_EXTENSION) {
                     Ok(CoverFormat::Jpeg)
-                } else if value.ends_with(".png") {
+                } else if has_extension(value, Self::PNG_EXTENSION) {
                     Ok(CoverFormat::Png)
+                } else if has_extension(value, Self::WEBP_EXTENSION) {
+                    Ok(CoverFormat::WebP)
+                } else if has_extension(value, Self::AVIF_EXTENSION) {
+                    Ok(CoverFormat::Avif)
                 } else {
                     Err(Error::UnsupportedFormatError)
                 }
@@ -98,3 +468,248 @@ impl TryFrom<&str> for CoverFormat {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use ffmpeg_next::codec;
+
+    use crate::format::{
+        AudioFormat, CoverFormat, Format, QobuzQualityId, YandexQuality, detect_format_from_url,
+    };
+
+    #[test]
+    fn qobuz_quality_id() {
+        let round_trip = |id| QobuzQualityId::try_from(AudioFormat::try_from(id).unwrap());
+        assert_eq!(
+            round_trip(QobuzQualityId::MP3_320).unwrap(),
+            QobuzQualityId::MP3_320
+        );
+        assert_eq!(
+            round_trip(QobuzQualityId::FLAC_24).unwrap(),
+            QobuzQualityId::FLAC_16
+        );
+        assert_eq!(
+            AudioFormat::try_from(QobuzQualityId::FLAC_24_192).unwrap(),
+            AudioFormat::Flac
+        );
+        assert!(AudioFormat::try_from(QobuzQualityId(1)).is_err());
+        assert!(QobuzQualityId::try_from(AudioFormat::Aac(256)).is_err());
+    }
+
+    #[test]
+    fn yandex_quality() {
+        assert_eq!(
+            AudioFormat::from(YandexQuality::Lossless),
+            AudioFormat::FlacMp4
+        );
+        assert_eq!(
+            AudioFormat::from(YandexQuality::High),
+            AudioFormat::Mp3(320)
+        );
+    }
+
+    #[test]
+    fn flac_mp4() {
+        assert_eq!(AudioFormat::FlacMp4.extension(), "m4a");
+        assert_eq!(AudioFormat::FlacMp4.mime_type(), AudioFormat::MP4_MIME_TYPE);
+        assert!(AudioFormat::FlacMp4.is_lossless());
+        assert!(!AudioFormat::Aac(256).is_lossless());
+    }
+
+    #[test]
+    fn alac() {
+        assert_eq!(AudioFormat::Alac.extension(), "m4a");
+        assert_eq!(AudioFormat::Alac.mime_type(), AudioFormat::MP4_MIME_TYPE);
+        assert!(AudioFormat::Alac.is_lossless());
+        assert_eq!(
+            AudioFormat::try_from("audio/mp4").unwrap(),
+            AudioFormat::Aac(0)
+        );
+    }
+
+    #[test]
+    fn ogg() {
+        assert_eq!(AudioFormat::Opus(128).extension(), "opus");
+        assert_eq!(AudioFormat::Vorbis(320).extension(), "ogg");
+        assert_eq!(AudioFormat::Opus(128).mime_type(), "audio/ogg");
+        assert_eq!(AudioFormat::Vorbis(320).muxer_name(), "ogg");
+        assert!(!AudioFormat::Opus(128).is_lossless());
+        for (value, format) in [
+            ("audio/ogg", AudioFormat::Vorbis(0)),
+            ("audio/opus", AudioFormat::Opus(0)),
+            ("track.opus", AudioFormat::Opus(0)),
+            ("track.ogg", AudioFormat::Vorbis(0)),
+        ] {
+            assert_eq!(AudioFormat::try_from(value).unwrap(), format);
+        }
+        assert_eq!(
+            detect_format_from_url("https://cdn.example/track.opus?token=1"),
+            Some(AudioFormat::Opus(0))
+        );
+    }
+
+    #[test]
+    fn pcm() {
+        assert_eq!(AudioFormat::Wav.extension(), "wav");
+        assert_eq!(AudioFormat::Aiff.extension(), "aiff");
+        assert!(AudioFormat::Aiff.is_lossless());
+        for (value, format) in [
+            ("audio/x-wav", AudioFormat::Wav),
+            ("audio/aiff", AudioFormat::Aiff),
+            ("track.wav", AudioFormat::Wav),
+            ("track.aif", AudioFormat::Aiff),
+        ] {
+            assert_eq!(AudioFormat::try_from(value).unwrap(), format);
+        }
+        assert!(QobuzQualityId::try_from(AudioFormat::Wav).is_err());
+    }
+
+    #[test]
+    fn cover_codec_id() {
+        for format in [
+            CoverFormat::Png,
+            CoverFormat::Jpeg,
+            CoverFormat::WebP,
+            CoverFormat::Avif,
+        ] {
+            assert_eq!(
+                CoverFormat::from_ffmpeg_codec_id(format.ffmpeg_codec_id()),
+                Some(format)
+            );
+        }
+        assert_eq!(CoverFormat::from_ffmpeg_codec_id(codec::Id::FLAC), None);
+    }
+
+    #[test]
+    fn constants() {
+        const DEFAULT_MIME: &str = AudioFormat::FLAC_MIME_TYPE;
+        assert_eq!(AudioFormat::default().mime_type(), DEFAULT_MIME);
+        assert_eq!(
+            AudioFormat::try_from(AudioFormat::MP3_MIME_TYPE).unwrap(),
+            AudioFormat::Mp3(0)
+        );
+        assert_eq!(
+            AudioFormat::try_from("track.flac").unwrap(),
+            AudioFormat::Flac
+        );
+        assert!(AudioFormat::try_from("trackflac").is_err());
+        assert_eq!(
+            CoverFormat::try_from(CoverFormat::PNG_MIME_TYPE).unwrap(),
+            CoverFormat::Png
+        );
+        assert_eq!(CoverFormat::Jpeg.extension(), CoverFormat::JPEG_EXTENSION);
+    }
+
+    #[test]
+    fn modern_covers() {
+        assert_eq!(CoverFormat::WebP.extension(), "webp");
+        assert_eq!(CoverFormat::Avif.mime_type(), "image/avif");
+        for (value, format) in [
+            ("image/webp", CoverFormat::WebP),
+            ("image/avif", CoverFormat::Avif),
+            ("cover.webp", CoverFormat::WebP),
+            ("cover.avif", CoverFormat::Avif),
+        ] {
+            assert_eq!(CoverFormat::try_from(value).unwrap(), format);
+        }
+        assert_ne!(CoverFormat::WebP, CoverFormat::Avif);
+    }
+
+    #[test]
+    fn default() {
+        assert_eq!(AudioFormat::default(), AudioFormat::Flac);
+        assert_eq!(CoverFormat::default(), CoverFormat::Jpeg);
+    }
+
+    #[test]
+    fn quality_rank() {
+        let mut formats = vec![
+            AudioFormat::Aac(96),
+            AudioFormat::Flac,
+            AudioFormat::Mp3(320),
+        ];
+        formats.sort_by_key(|f| std::cmp::Reverse(f.quality_rank()));
+        assert_eq!(
+            formats,
+            [
+                AudioFormat::Flac,
+                AudioFormat::Mp3(320),
+                AudioFormat::Aac(96)
+            ]
+        );
+    }
+
+    #[test]
+    fn serde() {
+        for (format, json) in [
+            (AudioFormat::Flac, r#"{"type":"flac"}"#),
+            (AudioFormat::FlacMp4, r#"{"type":"flac-mp4"}"#),
+            (AudioFormat::Mp3(320), r#"{"type":"mp3","bitrate":320}"#),
+            (AudioFormat::Aac(256), r#"{"type":"aac","bitrate":256}"#),
+            (AudioFormat::Opus(128), r#"{"type":"opus","bitrate":128}"#),
+        ] {
+            assert_eq!(serde_json::to_string(&format).unwrap(), json);
+            assert_eq!(serde_json::from_str::<AudioFormat>(json).unwrap(), format);
+            assert_eq!(AudioFormat::try_from(json).unwrap(), format);
+        }
+        assert_eq!(
+            serde_json::to_string(&CoverFormat::Jpeg).unwrap(),
+            r#"{"type":"jpeg"}"#
+        );
+        assert_eq!(
+            CoverFormat::try_from(r#"{"type":"webp"}"#).unwrap(),
+            CoverFormat::WebP
+        );
+        assert!(AudioFormat::try_from(r#"{"type":"Ogg"}"#).is_err());
+    }
+
+    #[test]
+    fn from_str() {
+        for format in [
+            AudioFormat::Flac,
+            AudioFormat::FlacMp4,
+            AudioFormat::Mp3(0),
+            AudioFormat::Mp3(320),
+            AudioFormat::Aac(256),
+            AudioFormat::Alac,
+            AudioFormat::Opus(0),
+            AudioFormat::Opus(160),
+            AudioFormat::Vorbis(320),
+            AudioFormat::Wav,
+            AudioFormat::Aiff,
+        ] {
+            assert_eq!(format.to_string().parse::<AudioFormat>().unwrap(), format);
+        }
+        assert_eq!(
+            "MP3:320".parse::<AudioFormat>().unwrap(),
+            AudioFormat::Mp3(320)
+        );
+        assert!("flac:16".parse::<AudioFormat>().is_err());
+        assert!("mp3:high".parse::<AudioFormat>().is_err());
+        assert!("ogg".parse::<AudioFormat>().is_err());
+    }
+
+    #[test]
+    fn format_from_url() {
+        assert_eq!(
+            detect_format_from_url("https://cdn.example/flac/track-12345.flac?token=a.mp3"),
+            Some(AudioFormat::Flac)
+        );
+        assert_eq!(
+            detect_format_from_url("https://cdn.example/mp3-320/track-12345"),
+            Some(AudioFormat::Mp3(320))
+        );
+        assert_eq!(
+            detect_format_from_url("https://cdn.example/track.M4A"),
+            Some(AudioFormat::Aac(0))
+        );
+        assert_eq!(detect_format_from_url("https://cdn.example/track"), None);
+    }
+
+    #[test]
+    fn is_bitrate_known() {
+        assert!(AudioFormat::Aac(256).is_bitrate_known());
+        assert!(!AudioFormat::Aac(0).is_bitrate_known());
+        assert!(!AudioFormat::Flac.is_bitrate_known());
+    }
+}
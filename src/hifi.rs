@@ -1,16 +1,125 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
-use crate::{AudioFormat, AudioStream, Error, SearchResults};
-use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
-use serde::Deserialize;
+use crate::{
+    Album, ArtistDetail, AudioFormat, AudioStream, Error, SearchResults, Track,
+    format::{HifiQuality, detect_format_from_url},
+    id::HifiTrackId,
+    retry::{RetryPolicy, send_with_retry},
+    util::{check_status, is_valid_isrc, isrc_eq, join_url, paginate},
+};
+use futures::Stream;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::{Deserialize, de::DeserializeOwned};
 use serde_json::Value;
-use url::Url;
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::RwLock;
 
 #[derive(Clone)]
 pub struct Hifi {
     client: reqwest::Client,
-    config: Config,
+    // Shared between clones, requests send with a snapshot taken when they
+    // start so host list updates never change hosts mid-request
+    config: Arc<RwLock<Config>>,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+    // Keyed by host base URL
+    breakers: Arc<Mutex<HashMap<String, CircuitBreaker>>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures after which a host is skipped
+    pub failure_threshold: u32,
+    /// How long a host is skipped before a single probe request is let through
+    pub recovery_timeout: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            recovery_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitStatus {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct CircuitState {
+    status: CircuitStatus,
+    failure_count: u32,
+    opened_at: Option<Instant>,
+}
+
+#[derive(Clone)]
+struct CircuitBreaker {
+    state: Arc<Mutex<CircuitState>>,
+    failure_threshold: u32,
+    recovery_timeout: Duration,
+}
+
+impl CircuitBreaker {
+    fn new(config: &CircuitBreakerConfig) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CircuitState {
+                status: CircuitStatus::Closed,
+                failure_count: 0,
+                opened_at: None,
+            })),
+            failure_threshold: config.failure_threshold,
+            recovery_timeout: config.recovery_timeout,
+        }
+    }
+
+    fn status(&self) -> CircuitStatus {
+        self.state.lock().unwrap().status
+    }
+
+    fn allow_request(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let expired = state
+            .opened_at
+            .is_some_and(|at| at.elapsed() >= self.recovery_timeout);
+        match state.status {
+            CircuitStatus::Closed => true,
+            // A half open host only lets one probe through, unless that probe
+            // was abandoned for longer than the recovery timeout
+            CircuitStatus::Open | CircuitStatus::HalfOpen if expired => {
+                state.status = CircuitStatus::HalfOpen;
+                state.opened_at = Some(Instant::now());
+                true
+            }
+            CircuitStatus::Open | CircuitStatus::HalfOpen => false,
+        }
+    }
+
+    fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.status = CircuitStatus::Closed;
+        state.failure_count = 0;
+        state.opened_at = None;
+    }
+
+    fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.failure_count += 1;
+        if state.status == CircuitStatus::HalfOpen || state.failure_count >= self.failure_threshold
+        {
+            state.status = CircuitStatus::Open;
+            state.opened_at = Some(Instant::now());
+        }
+    }
 }
 
 #[derive(Clone, Deserialize)]
@@ -18,90 +127,411 @@ pub struct Host {
     base_url: String,
 }
 
+impl Host {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+        }
+    }
+}
+
+/// Tracks requested per search page unless the config sets another limit
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+
 #[derive(Clone, Deserialize)]
-pub struct Config(pub Vec<Host>);
+#[serde(from = "ConfigFile")]
+pub struct Config {
+    pub hosts: Vec<Host>,
+    /// Results per search page
+    pub limit: usize,
+    /// Applies to every host before moving on to the next one
+    pub retry: RetryPolicy,
+}
+
+impl From<Vec<Host>> for Config {
+    fn from(hosts: Vec<Host>) -> Self {
+        Self {
+            hosts,
+            limit: DEFAULT_PAGE_SIZE,
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+// Older config files are a plain list of hosts
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConfigFile {
+    Hosts(Vec<Host>),
+    Full {
+        hosts: Vec<Host>,
+        limit: Option<usize>,
+        #[serde(default)]
+        retry: RetryPolicy,
+    },
+}
+
+impl From<ConfigFile> for Config {
+    fn from(value: ConfigFile) -> Self {
+        match value {
+            ConfigFile::Hosts(hosts) => hosts.into(),
+            ConfigFile::Full {
+                hosts,
+                limit,
+                retry,
+            } => Self {
+                hosts,
+                limit: limit.unwrap_or(DEFAULT_PAGE_SIZE),
+                retry,
+            },
+        }
+    }
+}
+
+impl Config {
+    pub fn new(hosts: Vec<Host>) -> Self {
+        hosts.into()
+    }
+
+    /// Reads either a JSON list of hosts or an object with `hosts` and
+    /// optionally `limit` and `retry`
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+}
 
 impl Hifi {
     pub fn new(config: Config) -> Self {
         Self {
             client: Client::new(),
-            config,
+            config: Arc::new(RwLock::new(config)),
+            circuit_breaker: None,
+            breakers: Arc::default(),
         }
     }
 
+    /// Skips hosts that keep failing until their recovery timeout passes
+    pub fn with_circuit_breaker(config: Config, cb_config: CircuitBreakerConfig) -> Self {
+        Self {
+            circuit_breaker: Some(cb_config),
+            ..Self::new(config)
+        }
+    }
+
+    fn breaker(&self, base_url: &str) -> Option<CircuitBreaker> {
+        let config = self.circuit_breaker.as_ref()?;
+        Some(
+            self.breakers
+                .lock()
+                .unwrap()
+                .entry(base_url.to_owned())
+                .or_insert_with(|| CircuitBreaker::new(config))
+                .clone(),
+        )
+    }
+
+    /// State of the circuit for `base_url`, `None` without a circuit breaker
+    pub fn circuit_status(&self, base_url: &str) -> Option<CircuitStatus> {
+        self.circuit_breaker.as_ref()?;
+        Some(
+            self.breakers
+                .lock()
+                .unwrap()
+                .get(base_url)
+                .map_or(CircuitStatus::Closed, |b| b.status()),
+        )
+    }
+
+    // A host that is removed and added again starts with a closed circuit
+    fn prune_breakers(&self, config: &Config) {
+        self.breakers
+            .lock()
+            .unwrap()
+            .retain(|url, _| config.hosts.iter().any(|h| &h.base_url == url));
+    }
+
+    /// Replaces the config without waiting for requests in flight, those
+    /// finish with the hosts they started with
+    pub async fn refresh_hosts(&self, config: Config) {
+        let mut current = self.config.write().await;
+        *current = config;
+        self.prune_breakers(&current);
+    }
+
+    pub async fn add_host(&self, host: Host) {
+        self.config.write().await.hosts.push(host);
+    }
+
+    pub async fn remove_host_by_url(&self, url: &str) {
+        let mut config = self.config.write().await;
+        config.hosts.retain(|h| h.base_url != url);
+        self.prune_breakers(&config);
+    }
+
+    pub fn service_name(&self) -> &'static str {
+        "hifi"
+    }
+
+    pub fn supported_formats(&self) -> Vec<AudioFormat> {
+        vec![AudioFormat::Flac, AudioFormat::Aac(0)]
+    }
+
     fn builder<S: AsRef<str>>(
         &self,
         url: &str,
         method: Method,
         path: S,
     ) -> Result<RequestBuilder, Error> {
-        Ok(self
-            .client
-            .request(method, Url::parse(url).and_then(|u| u.join(path.as_ref()))?))
+        Ok(self.client.request(method, join_url(url, path.as_ref())?))
     }
 
     pub async fn try_send(
         &self,
         build_request: impl Fn(&str) -> Result<RequestBuilder, Error>,
     ) -> Result<Response, Error> {
-        for host in &self.config.0 {
-            if let Ok(response) = build_request(&host.base_url)?.send().await
-                && response.status() == StatusCode::OK
-            {
-                return Ok(response);
+        self.send_to_hosts(build_request, true).await
+    }
+
+    /// Without `record_outcome` open circuits are still skipped, but the
+    /// response does not count for or against the host. Used for requests
+    /// that healthy hosts may not support.
+    async fn send_to_hosts(
+        &self,
+        build_request: impl Fn(&str) -> Result<RequestBuilder, Error>,
+        record_outcome: bool,
+    ) -> Result<Response, Error> {
+        // Not held while sending, retry sleeps would block host updates
+        let config = self.config.read().await.clone();
+        let mut last_status = None;
+        for host in &config.hosts {
+            let breaker = self.breaker(&host.base_url);
+            let allowed = match &breaker {
+                Some(breaker) if record_outcome => breaker.allow_request(),
+                Some(breaker) => breaker.status() == CircuitStatus::Closed,
+                None => true,
+            };
+            if !allowed {
+                continue;
+            }
+            let breaker = breaker.filter(|_| record_outcome);
+            // Client errors like an unknown track or a rejected token come
+            // from a working host, only transport and server errors count
+            // against it
+            match send_with_retry(&config.retry, build_request(&host.base_url)?).await {
+                Ok(response) if !response.status().is_server_error() => {
+                    if let Some(breaker) = breaker {
+                        breaker.record_success();
+                    }
+                    return check_status(response, self.service_name());
+                }
+                Ok(response) => last_status = Some(response.status()),
+                Err(_) => last_status = None,
+            }
+            if let Some(breaker) = breaker {
+                breaker.record_failure();
             }
         }
-        Err(Error::ServiceError("cannot find usable server".to_owned()))
+        Err(Error::ServiceError(match last_status {
+            Some(status) => format!("cannot find usable server, last one returned {}", status),
+            None => "cannot find usable server".to_owned(),
+        }))
     }
 
-    pub async fn search(&self, query: &str, _page: usize) -> Result<SearchResults, Error> {
+    async fn search_response(
+        &self,
+        query: &str,
+        page: usize,
+    ) -> Result<data::SearchResponse, Error> {
+        let limit = self.config.read().await.limit;
         let response = self
             .try_send(|url| {
                 Ok(self
                     .builder(url, Method::GET, "/search/")?
-                    .query(&[("s", query)]))
+                    .query(&[("s", query)])
+                    .query(&[("offset", page * limit), ("limit", limit)]))
             })
             .await?;
-        Ok(response.json::<data::SearchResponse>().await?.into())
+        Ok(response.json::<data::SearchResponse>().await?)
     }
 
-    pub async fn get_stream(&self, id: &str) -> Result<AudioStream, Error> {
-        let response = self
+    /// Falls back to a text search for `hint` (usually title and artist) or
+    /// the ISRC itself when the host does not support ISRC lookups, results
+    /// are always matched against the ISRC
+    pub async fn search_by_isrc(
+        &self,
+        isrc: &str,
+        hint: Option<&str>,
+    ) -> Result<Option<Track>, Error> {
+        if !is_valid_isrc(isrc) {
+            return Err(Error::InvalidIdError(isrc.to_owned()));
+        }
+        let find = |response: data::SearchResponse| {
+            response
+                .items
+                .into_iter()
+                .find(|t| t.isrc.as_deref().is_some_and(|i| isrc_eq(i, isrc)))
+                .map(Track::from)
+        };
+        // Hosts without ISRC lookups reject the parameter, which says
+        // nothing about their health
+        let by_isrc = self
+            .send_to_hosts(
+                |url| {
+                    Ok(self
+                        .builder(url, Method::GET, "/search/")?
+                        .query(&[("isrc", isrc)]))
+                },
+                false,
+            )
+            .await;
+        if let Ok(response) = by_isrc
+            && let Ok(response) = response.json::<data::SearchResponse>().await
+            && let Some(track) = find(response)
+        {
+            return Ok(Some(track));
+        }
+        Ok(find(self.search_response(hint.unwrap_or(isrc), 0).await?))
+    }
+
+    pub async fn search(&self, query: &str, page: usize) -> Result<SearchResults, Error> {
+        Ok(self.search_response(query, page).await?.into())
+    }
+
+    /// Fetches search pages on demand and yields their tracks one by one
+    pub fn search_all<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> impl Stream<Item = Result<Track, Error>> + 'a {
+        paginate(move |page| self.search(query, page))
+    }
+
+    // The proxy only searches tracks, albums and artists are collected
+    // from the track results
+    pub async fn search_albums(&self, query: &str, page: usize) -> Result<Vec<Album>, Error> {
+        let mut albums: Vec<Album> = vec![];
+        for track in self.search_response(query, page).await?.items {
+            if albums.iter().all(|a| a.id != track.album.id.to_string()) {
+                albums.push(track.into());
+            }
+        }
+        Ok(albums)
+    }
+
+    pub async fn search_artists(
+        &self,
+        query: &str,
+        page: usize,
+    ) -> Result<Vec<ArtistDetail>, Error> {
+        let mut artists: Vec<ArtistDetail> = vec![];
+        for track in self.search_response(query, page).await?.items {
+            for artist in track.artists.into_iter().chain([track.artist]) {
+                if artists.iter().all(|a| a.id != artist.id.to_string()) {
+                    artists.push(artist.into());
+                }
+            }
+        }
+        Ok(artists)
+    }
+
+    // The `/track/` endpoint responds with a JSON array:
+    // 0 - TIDAL track object, same shape as the search items
+    // 1 - playback info (audio quality, manifest mime type and manifest)
+    // 2 - object with `OriginalTrackUrl`, the direct stream url
+    async fn get_track_response(
+        &self,
+        id: &str,
+        quality: HifiQuality,
+    ) -> Result<Vec<Value>, Error> {
+        Ok(self
             .try_send(|url| {
                 Ok(self
                     .builder(url, Method::GET, "/track/")?
-                    .query(&[("id", id), ("quality", "LOSSLESS")]))
+                    .query(&[("id", id), ("quality", quality.as_str())]))
             })
             .await?
             .json::<Vec<Value>>()
+            .await?)
+    }
+
+    fn parse_track_response<T: DeserializeOwned>(
+        response: &[Value],
+        index: usize,
+    ) -> Result<T, Error> {
+        response
+            .get(index)
+            .and_then(|v| serde_json::from_value::<T>(v.clone()).ok())
+            .ok_or_else(|| Error::ServiceError("service did not return valid json".to_owned()))
+    }
+
+    pub async fn get_track(&self, id: &HifiTrackId) -> Result<Track, Error> {
+        let response = self
+            .get_track_response(id.as_str(), HifiQuality::default())
             .await?;
+        Ok(Self::parse_track_response::<data::Track>(&response, 0)?.into())
+    }
 
-        let Some(track_response) = response
-            .get(2)
-            .and_then(|v| serde_json::from_value::<data::TrackResponse>(v.clone()).ok())
-        else {
-            return Err(Error::ServiceError(
-                "service did not return valid json".to_owned(),
-            ));
-        };
+    pub async fn get_track_quality_info(
+        &self,
+        id: &HifiTrackId,
+    ) -> Result<Vec<AudioFormat>, Error> {
+        let response = self
+            .get_track_response(id.as_str(), HifiQuality::default())
+            .await?;
+        let track = Self::parse_track_response::<data::Track>(&response, 0)?;
+        let options = data::LosslessQualityOptions::from(&track);
 
+        let mut qualities = vec![HifiQuality::Low, HifiQuality::High];
+        if options.lossless {
+            qualities.push(HifiQuality::Lossless);
+        }
+        if options.hires {
+            qualities.push(HifiQuality::HiRes);
+        }
+        let mut formats = qualities
+            .into_iter()
+            .map(AudioFormat::from)
+            .collect::<Vec<_>>();
+        formats.sort_by_key(|f| std::cmp::Reverse(f.quality_rank()));
+        formats.dedup();
+        Ok(formats)
+    }
+
+    // The proxy falls back to a lower quality when the requested one is not
+    // available, so the format comes from the response rather than `quality`
+    pub async fn get_stream(
+        &self,
+        id: &HifiTrackId,
+        quality: HifiQuality,
+    ) -> Result<AudioStream, Error> {
+        let response = self.get_track_response(id.as_str(), quality).await?;
+        let track_response = Self::parse_track_response::<data::TrackResponse>(&response, 2)?;
+        let played_quality = Self::parse_track_response::<data::PlaybackInfo>(&response, 1)
+            .ok()
+            .and_then(|info| info.quality());
+        let format = detect_format_from_url(&track_response.original_track_url)
+            .unwrap_or_else(|| played_quality.unwrap_or(quality).into());
+
+        let retry = self.config.read().await.retry.clone();
         Ok(AudioStream {
-            response: self
-                .client
-                .get(track_response.original_track_url)
-                .send()
-                .await?,
-            format: AudioFormat::Flac,
+            response: check_status(
+                send_with_retry(&retry, self.client.get(track_response.original_track_url)).await?,
+                self.service_name(),
+            )?,
+            format,
+            md5: None,
         })
     }
 }
 
 mod data {
+    use crate::format::HifiQuality;
     use serde::Deserialize;
 
     #[derive(Debug, Deserialize)]
     pub struct SearchResponse {
         pub items: Vec<Track>,
+        #[serde(rename = "totalNumberOfItems")]
+        pub total: Option<usize>,
     }
 
     #[derive(Debug, Deserialize)]
@@ -111,20 +541,81 @@ mod data {
         pub url: String,
         pub duration: usize,
         pub artist: Artist,
+        #[serde(default)]
+        pub artists: Vec<Artist>,
         pub album: Album,
+        pub isrc: Option<String>,
+        #[serde(rename = "trackNumber")]
+        pub track_number: Option<u32>,
+        #[serde(rename = "audioQuality")]
+        pub audio_quality: Option<String>,
+        #[serde(rename = "mediaMetadata")]
+        pub media_metadata: Option<MediaMetadata>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct MediaMetadata {
+        #[serde(default)]
+        pub tags: Vec<String>,
+    }
+
+    #[derive(Debug, Default)]
+    pub struct LosslessQualityOptions {
+        pub lossless: bool,
+        pub hires: bool,
+    }
+
+    impl From<&Track> for LosslessQualityOptions {
+        fn from(value: &Track) -> Self {
+            let mut options = Self::default();
+            let tags = value.media_metadata.iter().flat_map(|m| &m.tags);
+            for quality in value.audio_quality.iter().chain(tags) {
+                match quality.as_str() {
+                    "LOSSLESS" => options.lossless = true,
+                    "HI_RES" | "HI_RES_LOSSLESS" | "HIRES_LOSSLESS" => {
+                        options.lossless = true;
+                        options.hires = true;
+                    }
+                    _ => {}
+                }
+            }
+            options
+        }
     }
 
     #[derive(Debug, Deserialize)]
     pub struct Artist {
         pub id: u64,
         pub name: String,
+        pub picture: Option<String>,
     }
 
     #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
     pub struct Album {
         pub id: u64,
         pub title: String,
         pub cover: String,
+        pub artist: Option<Artist>,
+        pub release_date: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PlaybackInfo {
+        pub audio_quality: String,
+    }
+
+    impl PlaybackInfo {
+        pub fn quality(&self) -> Option<HifiQuality> {
+            match self.audio_quality.as_str() {
+                "LOW" => Some(HifiQuality::Low),
+                "HIGH" => Some(HifiQuality::High),
+                "LOSSLESS" => Some(HifiQuality::Lossless),
+                "HI_RES" | "HI_RES_LOSSLESS" => Some(HifiQuality::HiRes),
+                _ => None,
+            }
+        }
     }
 
     #[derive(Debug, Deserialize)]
@@ -137,6 +628,8 @@ mod data {
         fn from(value: SearchResponse) -> Self {
             Self {
                 tracks: value.items.into_iter().map(crate::Track::from).collect(),
+                total: value.total,
+                cache_hit: false,
             }
         }
     }
@@ -148,15 +641,65 @@ mod data {
                 url: value.url,
                 title: value.title,
                 duration_ms: value.duration * 1000,
-                artists: vec![crate::Artist::from(value.artist)],
-                cover_url: format!(
-                    "https://resources.tidal.com/images/{}/750x750.jpg",
-                    value.album.cover.replace("-", "/")
-                ),
+                // `artist` is only the main artist, older hosts leave out
+                // the full list
+                artists: if value.artists.is_empty() {
+                    vec![crate::Artist::from(value.artist)]
+                } else {
+                    value.artists.into_iter().map(crate::Artist::from).collect()
+                },
+                cover_url: image_url(&value.album.cover),
+                cover_uri_template: Some(image_url_template(&value.album.cover)),
+                isrc: value.isrc,
+                album_label_id: None,
+                album: Some(value.album.title),
+                track_number: value.track_number,
+            }
+        }
+    }
+
+    impl From<Track> for crate::Album {
+        fn from(value: Track) -> Self {
+            let artist = value.album.artist.unwrap_or(value.artist);
+            Self {
+                id: value.album.id.to_string(),
+                url: format!("https://tidal.com/browse/album/{}", value.album.id),
+                title: value.album.title,
+                artists: vec![crate::Artist::from(artist)],
+                cover_url: image_url(&value.album.cover),
+                release_date: value.album.release_date,
+                track_count: 0,
+                disc_count: None,
+                max_sampling_rate_khz: None,
+                max_bit_depth: None,
+                tracks: vec![],
             }
         }
     }
 
+    impl From<Artist> for crate::ArtistDetail {
+        fn from(value: Artist) -> Self {
+            Self {
+                id: value.id.to_string(),
+                url: format!("https://tidal.com/browse/artist/{}", value.id),
+                name: value.name,
+                cover_url: value.picture.as_deref().map(image_url),
+                genres: vec![],
+            }
+        }
+    }
+
+    fn image_url_template(id: &str) -> String {
+        format!(
+            "https://resources.tidal.com/images/{}/%%.jpg",
+            id.replace("-", "/")
+        )
+    }
+
+    fn image_url(id: &str) -> String {
+        image_url_template(id).replace("%%", "750x750")
+    }
+
     impl From<Artist> for crate::Artist {
         fn from(value: Artist) -> Self {
             Self {
@@ -169,25 +712,243 @@ mod data {
 
 #[cfg(test)]
 mod test {
-    use crate::{hifi::Hifi, save_audio_stream, save_cover};
-    use std::path::Path;
-    use tokio::fs;
+    use crate::{
+        format::HifiQuality,
+        hifi::{CircuitBreakerConfig, CircuitStatus, Config, DEFAULT_PAGE_SIZE, Hifi, Host, data},
+        id::HifiTrackId,
+        no_op_progress,
+        retry::RetryPolicy,
+        save_audio_stream, save_cover,
+    };
+    use reqwest::Method;
+    use std::{
+        path::Path,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    #[tokio::test]
+    async fn refresh_hosts() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = Hifi::new(Config::new(vec![Host::new(format!(
+            "http://{}",
+            listener.local_addr().unwrap()
+        ))]));
+        let request = tokio::spawn({
+            let client = client.clone();
+            async move {
+                client
+                    .try_send(|url| client.builder(url, Method::GET, "/"))
+                    .await
+            }
+        });
+
+        // The refresh does not wait for the in-flight request, which still
+        // completes against the host list it started with
+        let (mut socket, _) = listener.accept().await.unwrap();
+        assert!(socket.read(&mut [0; 1024]).await.unwrap() > 0);
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            client.refresh_hosts(Config::new(vec![])),
+        )
+        .await
+        .unwrap();
+        assert!(client.config.read().await.hosts.is_empty());
+
+        socket
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+            .await
+            .unwrap();
+        assert!(request.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn circuit_breaker() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        tokio::spawn({
+            let requests = requests.clone();
+            async move {
+                loop {
+                    let (mut socket, _) = listener.accept().await.unwrap();
+                    assert!(socket.read(&mut [0; 1024]).await.unwrap() > 0);
+                    // Fails the first two requests
+                    let status = match requests.fetch_add(1, Ordering::SeqCst) {
+                        0 | 1 => "500 Internal Server Error",
+                        _ => "200 OK",
+                    };
+                    socket
+                        .write_all(
+                            format!(
+                                "HTTP/1.1 {}\r\nconnection: close\r\ncontent-length: 0\r\n\r\n",
+                                status
+                            )
+                            .as_bytes(),
+                        )
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+
+        let client = Hifi::with_circuit_breaker(
+            Config {
+                retry: RetryPolicy::none(),
+                ..Config::new(vec![Host::new(url.clone())])
+            },
+            CircuitBreakerConfig {
+                failure_threshold: 2,
+                recovery_timeout: Duration::from_millis(200),
+            },
+        );
+        let send = || client.try_send(|url| client.builder(url, Method::GET, "/"));
+
+        assert!(send().await.is_err());
+        assert_eq!(client.circuit_status(&url), Some(CircuitStatus::Closed));
+        assert!(send().await.is_err());
+        assert_eq!(client.circuit_status(&url), Some(CircuitStatus::Open));
+
+        // The open host is skipped without a request
+        assert!(send().await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(send().await.is_ok());
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        assert_eq!(client.circuit_status(&url), Some(CircuitStatus::Closed));
+        assert_eq!(Hifi::new(Config::new(vec![])).circuit_status(&url), None);
+    }
+
+    #[tokio::test]
+    async fn circuit_breaker_host_updates() {
+        // Nothing listens on the port once the listener is dropped
+        let url = {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let client = Hifi::with_circuit_breaker(
+            Config {
+                retry: RetryPolicy::none(),
+                ..Config::new(vec![Host::new(url.clone())])
+            },
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                ..Default::default()
+            },
+        );
+        let open = || async {
+            let _ = client
+                .try_send(|url| client.builder(url, Method::GET, "/"))
+                .await;
+            client.circuit_status(&url)
+        };
+
+        assert_eq!(open().await, Some(CircuitStatus::Open));
+        client.remove_host_by_url(&url).await;
+        client.add_host(Host::new(url.clone())).await;
+        assert_eq!(client.circuit_status(&url), Some(CircuitStatus::Closed));
+
+        assert_eq!(open().await, Some(CircuitStatus::Open));
+        client.refresh_hosts(Config::new(vec![])).await;
+        assert!(client.breakers.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn track_artists() {
+        let track = |artists: &str| {
+            let track: data::Track = serde_json::from_str(&format!(
+                r#"{{
+                    "id": 1,
+                    "title": "Title",
+                    "url": "http://www.tidal.com/track/1",
+                    "duration": 60,
+                    "artist": {{"id": 2, "name": "Main"}},
+                    {}
+                    "album": {{"id": 3, "title": "Album", "cover": "a-b-c"}}
+                }}"#,
+                artists
+            ))
+            .unwrap();
+            crate::Track::from(track)
+                .artists
+                .into_iter()
+                .map(|a| a.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            track(r#""artists": [{"id": 2, "name": "Main"}, {"id": 4, "name": "Featured"}],"#),
+            ["Main", "Featured"]
+        );
+        assert_eq!(track(""), ["Main"]);
+    }
+
+    #[test]
+    fn config_file() {
+        let config: Config =
+            serde_json::from_str(r#"[{"base_url": "https://hifi.example.com"}]"#).unwrap();
+        assert_eq!(config.hosts[0].base_url, "https://hifi.example.com");
+        assert_eq!(config.limit, DEFAULT_PAGE_SIZE);
+
+        let config: Config = serde_json::from_str(
+            r#"{"hosts": [{"base_url": "https://hifi.example.com"}], "limit": 50}"#,
+        )
+        .unwrap();
+        assert_eq!(config.hosts.len(), 1);
+        assert_eq!(config.limit, 50);
+        assert_eq!(config.retry, RetryPolicy::default());
+
+        let config: Config =
+            serde_json::from_str(r#"{"hosts": [], "retry": {"max_attempts": 5, "jitter": false}}"#)
+                .unwrap();
+        assert_eq!(config.limit, DEFAULT_PAGE_SIZE);
+        assert_eq!(
+            config.retry,
+            RetryPolicy {
+                max_attempts: 5,
+                jitter: false,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn playback_info() {
+        let response = vec![
+            serde_json::json!({}),
+            serde_json::json!({"audioQuality": "HIGH", "manifestMimeType": "application/vnd.tidal.bts"}),
+        ];
+        let info = Hifi::parse_track_response::<data::PlaybackInfo>(&response, 1).unwrap();
+        assert_eq!(info.quality(), Some(HifiQuality::High));
+        assert_eq!(HifiQuality::default(), HifiQuality::Lossless);
+    }
 
     #[tokio::test]
     async fn all() {
         let query = std::env::var("FRUITYGER_HIFI_QUERY").unwrap_or("periphery scarlet".to_owned());
         let client = Hifi::new(
-            serde_json::from_slice(
-                &fs::read(std::env::var("FRUITYGER_HIFI_CONFIG").unwrap_or("config.json".to_owned()))
-                    .await
-                    .unwrap(),
-            )
+            Config::from_file(Path::new(
+                &std::env::var("FRUITYGER_HIFI_CONFIG").unwrap_or("config.json".to_owned()),
+            ))
             .unwrap(),
         );
         let results = client.search(&query, 0).await.unwrap();
         let track = &results.tracks[0];
-        let stream = client.get_stream(&track.id).await.unwrap();
-        let _ = save_audio_stream(stream, Path::new("/tmp"), "hifi_test")
+        let stream = client
+            .get_stream(
+                &HifiTrackId::try_from(track.id.as_str()).unwrap(),
+                HifiQuality::default(),
+            )
+            .await
+            .unwrap();
+        let _ = save_audio_stream(stream, Path::new("/tmp"), "hifi_test", no_op_progress)
             .await
             .unwrap();
         let _ = save_cover(
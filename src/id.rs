@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::fmt;
+
+use serde::Serialize;
+use url::Url;
+
+use crate::error::Error;
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+// Takes the path segment following `segment`, so both `/track/{id}` and
+// `/album/{album}/track/{id}` style URLs work
+fn id_from_url(url: &Url, host_matches: fn(&str) -> bool, segment: &str) -> Result<String, Error> {
+    let invalid = || Error::InvalidIdError(url.to_string());
+    if !url.host_str().is_some_and(host_matches) {
+        return Err(invalid());
+    }
+    let mut segments = url.path_segments().ok_or_else(invalid)?;
+    segments.find(|s| *s == segment).ok_or_else(invalid)?;
+    segments
+        .next()
+        .filter(|id| is_valid_id(id))
+        .map(str::to_owned)
+        .ok_or_else(invalid)
+}
+
+macro_rules! service_id {
+    ($name:ident, $host_matches:expr, $segment:literal) => {
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl TryFrom<&Url> for $name {
+            type Error = Error;
+
+            fn try_from(value: &Url) -> Result<Self, Self::Error> {
+                id_from_url(value, $host_matches, $segment).map(Self)
+            }
+        }
+
+        /// Accepts either a bare ID or a link to the item
+        impl TryFrom<&str> for $name {
+            type Error = Error;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                if is_valid_id(value) {
+                    return Ok(Self(value.to_owned()));
+                }
+                let url = Url::parse(value).map_err(|_| Error::InvalidIdError(value.to_owned()))?;
+                Self::try_from(&url)
+            }
+        }
+    };
+}
+
+service_id!(
+    QobuzTrackId,
+    |host| matches!(host, "open.qobuz.com" | "play.qobuz.com"),
+    "track"
+);
+service_id!(
+    QobuzAlbumId,
+    |host| matches!(host, "open.qobuz.com" | "play.qobuz.com"),
+    "album"
+);
+service_id!(
+    YandexTrackId,
+    |host| host.starts_with("music.yandex."),
+    "track"
+);
+service_id!(
+    YandexAlbumId,
+    |host| host.starts_with("music.yandex."),
+    "album"
+);
+service_id!(
+    DeezerTrackId,
+    |host| host == "deezer.com" || host.ends_with(".deezer.com"),
+    "track"
+);
+service_id!(
+    HifiTrackId,
+    |host| host == "tidal.com" || host.ends_with(".tidal.com"),
+    "track"
+);
+
+/// Yandex radio station in `type:tag` form, e.g. `genre:rock`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(transparent)]
+pub struct StationId(String);
+
+impl StationId {
+    pub fn new(station_type: &str, tag: &str) -> Self {
+        Self(format!("{}:{}", station_type, tag))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for StationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<&str> for StationId {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.split_once(':') {
+            Some((station_type, tag))
+                if !station_type.is_empty()
+                    && !tag.is_empty()
+                    && !value.contains(['/', '?', '#']) =>
+            {
+                Ok(Self(value.to_owned()))
+            }
+            _ => Err(Error::InvalidIdError(value.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::id::{
+        DeezerTrackId, HifiTrackId, QobuzTrackId, StationId, YandexAlbumId, YandexTrackId,
+    };
+
+    #[test]
+    fn station_id() {
+        assert_eq!(
+            StationId::try_from("genre:rock").unwrap(),
+            StationId::new("genre", "rock")
+        );
+        assert!(StationId::try_from("genre").is_err());
+        assert!(StationId::try_from("genre:").is_err());
+        assert!(StationId::try_from("genre:rock/../x").is_err());
+    }
+
+    #[test]
+    fn from_url() {
+        assert_eq!(
+            QobuzTrackId::try_from("https://open.qobuz.com/track/12345")
+                .unwrap()
+                .as_str(),
+            "12345"
+        );
+        assert_eq!(
+            YandexTrackId::try_from("https://music.yandex.ru/album/111/track/222")
+                .unwrap()
+                .as_str(),
+            "222"
+        );
+        assert_eq!(
+            YandexAlbumId::try_from("https://music.yandex.ru/album/111/track/222")
+                .unwrap()
+                .as_str(),
+            "111"
+        );
+        assert_eq!(
+            HifiTrackId::try_from("https://tidal.com/browse/track/333")
+                .unwrap()
+                .as_str(),
+            "333"
+        );
+        assert_eq!(
+            DeezerTrackId::try_from("https://www.deezer.com/en/track/3135556")
+                .unwrap()
+                .as_str(),
+            "3135556"
+        );
+        assert_eq!(QobuzTrackId::try_from("12345").unwrap().as_str(), "12345");
+        assert!(QobuzTrackId::try_from("https://music.yandex.ru/album/111/track/222").is_err());
+        assert!(YandexTrackId::try_from("https://music.yandex.ru/album/111").is_err());
+        assert!(HifiTrackId::try_from("not an id").is_err());
+    }
+}
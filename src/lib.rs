@@ -1,30 +1,65 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
+pub mod album_art;
+#[cfg(not(target_arch = "wasm32"))]
+mod avio;
+pub mod cache;
+pub mod cover;
+pub mod deezer;
 pub mod error;
+pub mod events;
+pub mod export;
 pub mod format;
 pub mod hifi;
+pub mod id;
+pub mod lyrics;
+mod metadata;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod multipart;
+pub mod playlist;
 pub mod qobuz;
+pub mod retry;
+pub mod service;
+pub mod template;
+#[cfg(any(test, feature = "test-fixtures"))]
+pub mod test_fixtures;
+pub mod util;
+pub mod verify;
 pub mod yandex;
 
-use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Cursor;
+use std::{
+    collections::HashSet,
+    ops::{Add, AddAssign, Index},
+    path::{Path, PathBuf},
+};
 
+#[cfg(not(target_arch = "wasm32"))]
+use bytes::Bytes;
 use ffmpeg_next::{
     Dictionary, codec, encoder,
     ffi::AV_DISPOSITION_ATTACHED_PIC,
     format::context::{Input, Output},
     media,
 };
-use futures::TryStreamExt;
+use futures::{Stream, TryStreamExt};
 use reqwest::{Response, header};
-use serde::Serialize;
-use tokio::fs::File;
+use serde::{Deserialize, Serialize};
+use tokio::{fs::File, io::AsyncWriteExt};
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::avio::{IoContext, MemoryInput, MemoryOutput};
 use crate::{
+    cover::{CoverArtType, RemuxWarning},
     error::Error,
     format::{AudioFormat, CoverFormat, Format},
+    verify::IntegrityCheck,
 };
 
+pub use metadata::Metadata;
+
 #[macro_export]
 macro_rules! const_headers {
     ($slice:expr) => {{
@@ -37,40 +72,103 @@ macro_rules! const_headers {
     }};
 }
 
-macro_rules! metadata_option {
-    (($metadata:ident, $dict:ident) $(, $key:ident)*) => {
-        $(
-            if let Some(v) = $metadata.$key {
-                $dict.set(stringify!($key), &v);
-            }
-        )*
-    };
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub tracks: Vec<Track>,
+    /// Number of matches across all pages, if the service reports it
+    #[serde(default)]
+    pub total: Option<usize>,
+    /// Set when the results were served by [`cache::SearchCache`]
+    #[serde(default)]
+    pub cache_hit: bool,
 }
 
-#[derive(Default)]
-pub struct Metadata {
-    pub album: Option<String>,
-    pub album_artist: Option<String>,
-    pub artist: String,
-    pub composer: Option<String>,
-    pub copyright: Option<String>,
-    pub creation_time: Option<String>,
-    pub date: Option<String>,
-    pub disc: Option<String>,
-    pub genre: Option<String>,
-    pub language: Option<String>,
-    pub performer: Option<String>,
-    pub publisher: Option<String>,
-    pub title: String,
-    pub track: Option<String>,
+impl SearchResults {
+    /// Drops tracks whose ISRC already appeared earlier in the results,
+    /// tracks without an ISRC are always kept
+    pub fn deduplicate_by_isrc(&mut self) {
+        let mut seen = HashSet::new();
+        self.tracks.retain(|t| match &t.isrc {
+            Some(isrc) => seen.insert(util::normalize_isrc(isrc)),
+            None => true,
+        });
+    }
 }
 
-#[derive(Debug)]
-pub struct SearchResults {
-    pub tracks: Vec<Track>,
+// Empty results know their total, so folding pages onto the default keeps
+// the summed total
+impl Default for SearchResults {
+    fn default() -> Self {
+        Self {
+            tracks: vec![],
+            total: Some(0),
+            cache_hit: false,
+        }
+    }
+}
+
+impl From<Vec<Track>> for SearchResults {
+    fn from(tracks: Vec<Track>) -> Self {
+        Self {
+            tracks,
+            total: None,
+            cache_hit: false,
+        }
+    }
+}
+
+impl IntoIterator for SearchResults {
+    type Item = Track;
+    type IntoIter = std::vec::IntoIter<Track>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tracks.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SearchResults {
+    type Item = &'a Track;
+    type IntoIter = std::slice::Iter<'a, Track>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tracks.iter()
+    }
+}
+
+impl Extend<Track> for SearchResults {
+    fn extend<T: IntoIterator<Item = Track>>(&mut self, iter: T) {
+        self.tracks.extend(iter);
+    }
+}
+
+impl Add for SearchResults {
+    type Output = SearchResults;
+
+    fn add(mut self, rhs: SearchResults) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+// Merged results only count as a cache hit if both sides were, and only
+// have a total if both sides do
+impl AddAssign for SearchResults {
+    fn add_assign(&mut self, rhs: SearchResults) {
+        self.cache_hit = self.cache_hit && rhs.cache_hit;
+        self.total = self.total.zip(rhs.total).map(|(a, b)| a + b);
+        self.tracks.extend(rhs.tracks);
+    }
 }
 
-#[derive(Clone, Debug, Serialize)]
+impl Index<usize> for SearchResults {
+    type Output = Track;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.tracks[index]
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Track {
     pub id: String,
     pub url: String,
@@ -78,37 +176,320 @@ pub struct Track {
     pub duration_ms: usize,
     pub artists: Vec<Artist>,
     pub cover_url: String,
+    /// Cover URL with a `%%` placeholder for the size, for services that have one
+    pub cover_uri_template: Option<String>,
+    pub isrc: Option<String>,
+    pub album_label_id: Option<String>,
+    pub album: Option<String>,
+    pub track_number: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrackDisplayFormat {
+    /// `Artist - Title`
+    ArtistTitle,
+    /// `Title (Artist)`
+    TitleArtist,
+    /// `Artist - Title [Album, Track N] [MM:SS]`
+    Full,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MultiArtistSeparator {
+    #[default]
+    Ampersand,
+    Comma,
+}
+
+impl MultiArtistSeparator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ampersand => " & ",
+            Self::Comma => ", ",
+        }
+    }
+}
+
+impl Track {
+    pub fn to_display_string(&self, format: TrackDisplayFormat) -> String {
+        self.to_display_string_with(format, MultiArtistSeparator::default())
+    }
+
+    pub fn to_display_string_with(
+        &self,
+        format: TrackDisplayFormat,
+        separator: MultiArtistSeparator,
+    ) -> String {
+        let artists = self.artist_names_joined(separator.as_str());
+        match format {
+            TrackDisplayFormat::ArtistTitle => format!("{} - {}", artists, self.title),
+            TrackDisplayFormat::TitleArtist => format!("{} ({})", self.title, artists),
+            TrackDisplayFormat::Full => {
+                let mut display = format!("{} - {}", artists, self.title);
+                let album = [
+                    self.album.clone(),
+                    self.track_number.map(|n| format!("Track {}", n)),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+                if !album.is_empty() {
+                    display += &format!(" [{}]", album.join(", "));
+                }
+                display + &format!(" [{}]", self.duration_formatted())
+            }
+        }
+    }
+
+    pub fn artist_names_joined(&self, separator: &str) -> String {
+        self.artists
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    pub fn primary_artist(&self) -> Option<&Artist> {
+        self.artists.first()
+    }
+
+    /// Name of the first artist, or `Unknown` when the service returned none
+    pub fn primary_artist_name(&self) -> &str {
+        self.primary_artist()
+            .map(|a| a.name.as_str())
+            .unwrap_or("Unknown")
+    }
+
+    pub fn cover_url_at_size(&self, width: u32, height: u32) -> Option<String> {
+        self.cover_uri_template
+            .as_ref()
+            .map(|template| template.replace("%%", &format!("{}x{}", width, height)))
+    }
+
+    pub fn duration_secs(&self) -> f64 {
+        self.duration_ms as f64 / 1000.0
+    }
+
+    pub fn duration_formatted(&self) -> String {
+        format_duration_ms(self.duration_ms as u64)
+    }
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Artist {
     pub id: String,
     pub name: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Album {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub artists: Vec<Artist>,
+    pub cover_url: String,
+    pub release_date: Option<String>,
+    pub track_count: usize,
+    pub disc_count: Option<usize>,
+    /// Highest sampling rate of the album's tracks in kHz, if the service
+    /// reports it
+    #[serde(default)]
+    pub max_sampling_rate_khz: Option<f64>,
+    #[serde(default)]
+    pub max_bit_depth: Option<u32>,
+    pub tracks: Vec<Track>,
+}
+
+impl Album {
+    pub fn total_duration_ms(&self) -> u64 {
+        self.tracks.iter().map(|t| t.duration_ms as u64).sum()
+    }
+
+    pub fn total_duration_formatted(&self) -> String {
+        format_duration_ms(self.total_duration_ms())
+    }
+}
+
+fn format_duration_ms(ms: u64) -> String {
+    let secs = ms / 1000;
+    match secs / 3600 {
+        0 => format!("{:02}:{:02}", secs / 60, secs % 60),
+        hours => format!("{}:{:02}:{:02}", hours, secs / 60 % 60, secs % 60),
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Playlist {
+    pub id: String,
+    pub url: String,
+    pub name: String,
+    pub cover_url: Option<String>,
+    pub tracks: Vec<Track>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArtistDetail {
+    pub id: String,
+    pub url: String,
+    pub name: String,
+    pub cover_url: Option<String>,
+    pub genres: Vec<String>,
+}
+
+// Not `Clone`, the response body can only be consumed once
 pub struct AudioStream {
     pub response: Response,
     pub format: AudioFormat,
+    pub md5: Option<String>,
+}
+
+impl AudioStream {
+    pub fn into_parts(self) -> (Response, AudioFormat) {
+        (self.response, self.format)
+    }
+
+    pub fn content_length(&self) -> Option<u64> {
+        self.response.content_length()
+    }
+
+    pub fn url(&self) -> &reqwest::Url {
+        self.response.url()
+    }
 }
 
+// Only reads the URL and headers, the body is left untouched
+impl std::fmt::Debug for AudioStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let content_length = self
+            .response
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok()?.parse::<u64>().ok());
+        f.debug_struct("AudioStream")
+            .field("format", &self.format)
+            .field("url", &format_args!("{}", self.response.url()))
+            .field("content_length", &content_length)
+            .field("md5", &self.md5)
+            .finish()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SaveOptions {
+    pub verify_integrity: bool,
+}
+
+/// Suggested value for [`SaveCoverOptions::max_size_bytes`], real covers
+/// stay well below this even at original resolution
+pub const DEFAULT_MAX_COVER_SIZE_BYTES: u64 = 50 * 1024 * 1024;
+
+#[derive(Clone, Debug, Default)]
+pub struct SaveCoverOptions {
+    pub max_size_bytes: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DownloadProgress {
+    pub bytes_downloaded: u64,
+    pub total_bytes: Option<u64>,
+    pub chunk_size: usize,
+}
+
+/// Covers the target container cannot distinguish are skipped and reported
+/// as warnings, unreadable covers are skipped silently
 pub fn remux(
     dir: &Path,
     audio_path: &Path,
-    cover_path: Option<&Path>,
+    covers: Vec<(PathBuf, CoverArtType)>,
     target_audio_format: AudioFormat,
     target_filename: &str,
     metadata: Metadata,
-) -> Result<PathBuf, Error> {
-    let input_audio = ffmpeg_next::format::input(&audio_path)?;
-    let input_cover = cover_path.and_then(|c| ffmpeg_next::format::input(&c).ok());
+) -> Result<(PathBuf, Vec<RemuxWarning>), Error> {
+    let mut input_audio = ffmpeg_next::format::input(&audio_path)?;
+    let mut warnings = vec![];
+    let mut input_covers = vec![];
+    for (path, cover_type) in covers {
+        if !cover_type.is_supported_by(&target_audio_format) {
+            warnings.push(RemuxWarning::CoverTypeNotSupported(cover_type));
+        } else if let Ok(input) = ffmpeg_next::format::input(&path) {
+            check_cover_format(&input, &path)?;
+            input_covers.push((input, cover_type));
+        }
+    }
 
     let output_path = dir.join(format!(
         "{}.{}",
         target_filename,
         target_audio_format.extension()
     ));
-    let mut output = ffmpeg_next::format::output(&output_path)?;
+    let mut output =
+        ffmpeg_next::format::output_as(&output_path, target_audio_format.muxer_name())?;
+    mux(
+        &mut input_audio,
+        input_covers.iter_mut().map(|(i, t)| (i, &*t)).collect(),
+        &mut output,
+        metadata,
+    )?;
+    Ok((output_path, warnings))
+}
+
+// Catches covers saved under the wrong extension before they get embedded
+fn check_cover_format(input: &Input, path: &Path) -> Result<(), Error> {
+    let Ok(suggested) = CoverFormat::try_from(path.to_string_lossy().as_ref()) else {
+        return Ok(());
+    };
+    let detected = input
+        .streams()
+        .find(|s| s.parameters().medium() == media::Type::Video)
+        .and_then(|s| CoverFormat::from_ffmpeg_codec_id(s.parameters().id()));
+    match detected {
+        Some(detected) if detected != suggested => Err(Error::CoverFormatMismatch {
+            detected,
+            file_extension_suggested: suggested,
+        }),
+        _ => Ok(()),
+    }
+}
 
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remux_to_memory(
+    audio_data: Bytes,
+    cover_data: Option<Bytes>,
+    target_format: AudioFormat,
+    metadata: Metadata,
+) -> Result<Bytes, Error> {
+    let mut buffer = Cursor::new(Vec::with_capacity(
+        audio_data.len() + cover_data.as_ref().map_or(0, Bytes::len),
+    ));
+    let mut audio_reader = Cursor::new(audio_data);
+    let mut cover_reader = cover_data.map(Cursor::new);
+    {
+        let mut input_audio = MemoryInput::new(IoContext::reader(&mut audio_reader)?)?;
+        let mut input_cover = cover_reader
+            .as_mut()
+            .and_then(|c| IoContext::reader(c).and_then(MemoryInput::new).ok());
+        let mut output =
+            MemoryOutput::new(IoContext::writer(&mut buffer)?, target_format.muxer_name())?;
+        mux(
+            &mut input_audio,
+            input_cover
+                .as_deref_mut()
+                .map(|i| (i, &CoverArtType::FrontCover))
+                .into_iter()
+                .collect(),
+            &mut output,
+            metadata,
+        )?;
+    }
+    Ok(Bytes::from(buffer.into_inner()))
+}
+
+fn mux(
+    input_audio: &mut Input,
+    input_covers: Vec<(&mut Input, &CoverArtType)>,
+    output: &mut Output,
+    metadata: Metadata,
+) -> Result<(), Error> {
     let map_first_stream = |input: &Input, output: &mut Output, media_type: media::Type| {
         for stream in input.streams() {
             if stream.parameters().medium() != media_type {
@@ -128,36 +509,27 @@ pub fn remux(
     };
 
     let (input_audio_index, output_audio_index) =
-        map_first_stream(&input_audio, &mut output, media::Type::Audio)?;
+        map_first_stream(input_audio, output, media::Type::Audio)?;
     let mut sources = vec![(input_audio, input_audio_index, output_audio_index)];
-    if let Some(input_cover) = input_cover {
+    for (input_cover, cover_type) in input_covers {
         let (input_cover_index, output_cover_index) =
-            map_first_stream(&input_cover, &mut output, media::Type::Video)?;
+            map_first_stream(input_cover, output, media::Type::Video)?;
+        let mut cover_metadata = Dictionary::new();
+        cover_metadata.set("comment", cover_type.ffmpeg_comment());
+        if let CoverArtType::Other(description) = cover_type {
+            cover_metadata.set("title", description);
+        }
+        output
+            .stream_mut(output_cover_index)
+            .unwrap()
+            .set_metadata(cover_metadata);
         sources.push((input_cover, input_cover_index, output_cover_index));
     }
 
-    let mut dict = Dictionary::new();
-    dict.set("title", &metadata.title);
-    dict.set("artist", &metadata.artist);
-    metadata_option!(
-        (metadata, dict),
-        album,
-        album_artist,
-        composer,
-        copyright,
-        creation_time,
-        date,
-        disc,
-        genre,
-        language,
-        performer,
-        publisher,
-        track
-    );
-    output.set_metadata(dict);
+    output.set_metadata(metadata.to_ffmpeg_dict());
     output.write_header()?;
 
-    for (mut input, input_index, output_index) in sources {
+    for (input, input_index, output_index) in sources {
         for (stream, mut packet) in input.packets() {
             if stream.index() != input_index {
                 continue;
@@ -168,63 +540,491 @@ pub fn remux(
             );
             packet.set_stream(output_index);
             packet.set_position(-1);
-            packet.write_interleaved(&mut output)?;
+            packet.write_interleaved(output)?;
         }
     }
 
     output.write_trailer()?;
-    Ok(output_path)
+    Ok(())
 }
 
 pub async fn save_cover(
     response: Response,
     dir: &Path,
     filename: &str,
+) -> Result<(PathBuf, CoverFormat), Error> {
+    save_cover_with_options(response, dir, filename, &SaveCoverOptions::default()).await
+}
+
+pub async fn save_cover_with_options(
+    response: Response,
+    dir: &Path,
+    filename: &str,
+    options: &SaveCoverOptions,
 ) -> Result<(PathBuf, CoverFormat), Error> {
     let format = CoverFormat::try_from(
         response
             .headers()
             .get(header::CONTENT_TYPE)
             .and_then(|v| v.to_str().ok())
-            .unwrap_or("image/jpeg"),
+            .unwrap_or(CoverFormat::JPEG_MIME_TYPE),
     )?;
     let path = dir.join(format!("{}.{}", filename, format.extension()));
-    save(response, &path).await?;
+    save_with_limit(response, &path, options.max_size_bytes, no_op_progress).await?;
     Ok((path, format))
 }
 
+/// Progress callback that ignores every update, see [`save_audio_stream`]
+pub fn no_op_progress(_bytes: u64, _total: Option<u64>) {}
+
+/// `progress` is called after every chunk with the bytes written so far
+/// and the total size from `Content-Length`, if the server sent one
 pub async fn save_audio_stream(
     audio_stream: AudioStream,
     dir: &Path,
     filename: &str,
+    progress: impl Fn(u64, Option<u64>) + Send,
+) -> Result<PathBuf, Error> {
+    save_audio_stream_with_progress(
+        audio_stream,
+        dir,
+        filename,
+        &SaveOptions::default(),
+        progress,
+    )
+    .await
+}
+
+pub async fn save_audio_stream_with_options(
+    audio_stream: AudioStream,
+    dir: &Path,
+    filename: &str,
+    options: &SaveOptions,
+) -> Result<PathBuf, Error> {
+    save_audio_stream_with_progress(audio_stream, dir, filename, options, no_op_progress).await
+}
+
+async fn save_audio_stream_with_progress(
+    audio_stream: AudioStream,
+    dir: &Path,
+    filename: &str,
+    options: &SaveOptions,
+    progress: impl Fn(u64, Option<u64>) + Send,
 ) -> Result<PathBuf, Error> {
     let path = dir.join(format!("{}.{}", filename, audio_stream.format.extension()));
-    save(audio_stream.response, &path).await?;
+    save(audio_stream.response, &path, progress).await?;
+    if options.verify_integrity
+        && let Some(expected) = audio_stream.md5
+    {
+        let actual = IntegrityCheck::md5(&path).await?;
+        if !actual.eq_ignore_ascii_case(&expected) {
+            tokio::fs::remove_file(&path).await?;
+            return Err(Error::IntegrityError {
+                path,
+                expected,
+                actual,
+            });
+        }
+    }
     Ok(path)
 }
 
-pub async fn save(response: Response, path: &Path) -> Result<(), Error> {
+pub fn save_audio_stream_streaming(
+    audio_stream: AudioStream,
+    path: &Path,
+) -> impl Stream<Item = Result<DownloadProgress, Error>> {
+    let path = path.to_path_buf();
+    let total_bytes = audio_stream.response.content_length();
+    futures::stream::try_unfold(
+        (audio_stream.response.bytes_stream(), None, 0),
+        move |(mut stream, file, bytes_downloaded)| {
+            let path = path.clone();
+            async move {
+                let mut file = match file {
+                    Some(file) => file,
+                    None => File::create(&path).await?,
+                };
+                let Some(chunk) = stream.try_next().await? else {
+                    file.flush().await?;
+                    return Ok(None);
+                };
+                tokio::io::copy(&mut chunk.as_ref(), &mut file).await?;
+                let progress = DownloadProgress {
+                    bytes_downloaded: bytes_downloaded + chunk.len() as u64,
+                    total_bytes,
+                    chunk_size: chunk.len(),
+                };
+                let bytes_downloaded = progress.bytes_downloaded;
+                Ok(Some((progress, (stream, Some(file), bytes_downloaded))))
+            }
+        },
+    )
+}
+
+pub async fn save(
+    response: Response,
+    path: &Path,
+    progress: impl Fn(u64, Option<u64>) + Send,
+) -> Result<(), Error> {
+    save_with_limit(response, path, None, progress).await
+}
+
+pub(crate) async fn save_with_limit(
+    response: Response,
+    path: &Path,
+    limit: Option<u64>,
+    progress: impl Fn(u64, Option<u64>) + Send,
+) -> Result<(), Error> {
+    let total = response.content_length();
+    if let (Some(limit), Some(actual)) = (limit, total)
+        && actual > limit
+    {
+        return Err(Error::SizeExceeded { actual, limit });
+    }
+
     let mut stream = response.bytes_stream();
     let mut file = File::create(path).await?;
+    let mut written = 0;
     while let Some(chunk) = stream.try_next().await? {
+        written += chunk.len() as u64;
+        if let Some(limit) = limit
+            && written > limit
+        {
+            drop(file);
+            tokio::fs::remove_file(path).await?;
+            return Err(Error::SizeExceeded {
+                actual: written,
+                limit,
+            });
+        }
         tokio::io::copy(&mut chunk.as_ref(), &mut file).await?;
+        progress(written, total);
     }
     Ok(())
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{Metadata, format::AudioFormat, remux};
-    use std::path::Path;
+    use crate::{
+        Album, Artist, AudioStream, Metadata, MultiArtistSeparator, SaveCoverOptions, SaveOptions,
+        SearchResults, Track, TrackDisplayFormat,
+        cover::{CoverArtType, RemuxWarning},
+        error::Error,
+        format::AudioFormat,
+        remux, remux_to_memory, save_audio_stream, save_audio_stream_streaming,
+        save_audio_stream_with_options, save_cover_with_options,
+    };
+    use futures::TryStreamExt;
+    use std::path::{Path, PathBuf};
+
+    fn audio_stream(chunks: Vec<&'static [u8]>) -> AudioStream {
+        let body = reqwest::Body::wrap_stream(futures::stream::iter(
+            chunks.into_iter().map(Ok::<_, std::io::Error>),
+        ));
+        AudioStream {
+            response: http::Response::new(body).into(),
+            format: AudioFormat::Flac,
+            md5: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn audio_stream_parts() {
+        let stream = AudioStream {
+            response: http::Response::new(reqwest::Body::from("fruityger!")).into(),
+            format: AudioFormat::Mp3(320),
+            md5: None,
+        };
+        assert_eq!(stream.content_length(), Some(10));
+        let (response, format) = stream.into_parts();
+        assert!(matches!(format, AudioFormat::Mp3(320)));
+        assert_eq!(response.bytes().await.unwrap(), "fruityger!");
+    }
+
+    #[tokio::test]
+    async fn audio_stream_debug() {
+        let response = http::Response::builder()
+            .header("content-length", "10")
+            .body(reqwest::Body::from("fruityger!"))
+            .unwrap();
+        let stream = AudioStream {
+            response: response.into(),
+            format: AudioFormat::Mp3(320),
+            md5: None,
+        };
+        assert_eq!(
+            format!("{:?}", stream),
+            "AudioStream { format: Mp3(320), url: http://no.url.provided.local/, content_length: Some(10), md5: None }"
+        );
+        let (response, _) = stream.into_parts();
+        assert_eq!(response.bytes().await.unwrap(), "fruityger!");
+    }
+
+    #[tokio::test]
+    async fn save_audio_stream_progress() {
+        let updates = std::sync::Mutex::new(vec![]);
+        let path = save_audio_stream(
+            audio_stream(vec![b"hello ", b"world"]),
+            &std::env::temp_dir(),
+            "fruityger_progress",
+            |bytes, total| updates.lock().unwrap().push((bytes, total)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(*updates.lock().unwrap(), [(6, None), (11, None)]);
+        assert_eq!(std::fs::read(path).unwrap(), b"hello world");
+
+        let stream = AudioStream {
+            response: http::Response::new(reqwest::Body::from("hello")).into(),
+            format: AudioFormat::Flac,
+            md5: None,
+        };
+        let updates = std::sync::Mutex::new(vec![]);
+        save_audio_stream(
+            stream,
+            &std::env::temp_dir(),
+            "fruityger_progress_total",
+            |bytes, total| updates.lock().unwrap().push((bytes, total)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(*updates.lock().unwrap(), [(5, Some(5))]);
+    }
+
+    #[tokio::test]
+    async fn save_audio_stream_verify_integrity() {
+        let options = SaveOptions {
+            verify_integrity: true,
+        };
+        let stream = AudioStream {
+            md5: Some("5eb63bbbe01eeed093cb22bb8f5acdc3".to_owned()),
+            ..audio_stream(vec![b"hello ", b"world"])
+        };
+        save_audio_stream_with_options(stream, &std::env::temp_dir(), "fruityger_ok", &options)
+            .await
+            .unwrap();
+
+        let stream = AudioStream {
+            md5: Some("d41d8cd98f00b204e9800998ecf8427e".to_owned()),
+            ..audio_stream(vec![b"hello ", b"world"])
+        };
+        let error = save_audio_stream_with_options(
+            stream,
+            &std::env::temp_dir(),
+            "fruityger_bad",
+            &options,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(&error, Error::IntegrityError { actual, .. } if actual == "5eb63bbbe01eeed093cb22bb8f5acdc3")
+        );
+        let Error::IntegrityError { path, .. } = error else {
+            unreachable!()
+        };
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn save_cover_size_limit() {
+        let options = SaveCoverOptions {
+            max_size_bytes: Some(8),
+        };
+        let dir = std::env::temp_dir();
+
+        let response = http::Response::new(reqwest::Body::from("hello world")).into();
+        let error = save_cover_with_options(response, &dir, "fruityger_cover_length", &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::SizeExceeded {
+                actual: 11,
+                limit: 8
+            }
+        ));
+
+        let response = audio_stream(vec![b"hello ", b"world"]).response;
+        let error = save_cover_with_options(response, &dir, "fruityger_cover_chunked", &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::SizeExceeded {
+                actual: 11,
+                limit: 8
+            }
+        ));
+        assert!(!dir.join("fruityger_cover_chunked.jpg").exists());
+    }
+
+    #[test]
+    fn display_string() {
+        let mut track = Track::example();
+        assert_eq!(
+            track.to_display_string(TrackDisplayFormat::ArtistTitle),
+            "Test Artist - Test Track"
+        );
+        assert_eq!(
+            track.to_display_string(TrackDisplayFormat::Full),
+            "Test Artist - Test Track [Test Album, Track 1] [03:35]"
+        );
+
+        track.artists.push(Artist {
+            id: "1000002".to_owned(),
+            name: "Other Artist".to_owned(),
+        });
+        track.album = None;
+        assert_eq!(
+            track.to_display_string(TrackDisplayFormat::TitleArtist),
+            "Test Track (Test Artist & Other Artist)"
+        );
+        assert_eq!(
+            track.to_display_string_with(TrackDisplayFormat::Full, MultiArtistSeparator::Comma),
+            "Test Artist, Other Artist - Test Track [Track 1] [03:35]"
+        );
+    }
+
+    #[test]
+    fn artist_names() {
+        let mut track = Track::example();
+        track.artists.push(Artist {
+            id: "1000002".to_owned(),
+            name: "Other Artist".to_owned(),
+        });
+        assert_eq!(
+            track.artist_names_joined(" / "),
+            "Test Artist / Other Artist"
+        );
+        assert_eq!(track.primary_artist().unwrap().id, "1000001");
+        assert_eq!(track.primary_artist_name(), "Test Artist");
+
+        track.artists.clear();
+        assert_eq!(track.artist_names_joined(", "), "");
+        assert!(track.primary_artist().is_none());
+        assert_eq!(track.primary_artist_name(), "Unknown");
+    }
+
+    #[test]
+    fn durations() {
+        let track = Track::example();
+        assert_eq!(track.duration_secs(), 215.0);
+        assert_eq!(track.duration_formatted(), "03:35");
+
+        let album = Album {
+            tracks: vec![track; 20],
+            ..Album::example()
+        };
+        assert_eq!(album.total_duration_ms(), 4_300_000);
+        assert_eq!(album.total_duration_formatted(), "1:11:40");
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let results = SearchResults::example(2);
+        let json = serde_json::to_string(&results).unwrap();
+        let parsed: SearchResults = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.tracks.len(), 2);
+        assert_eq!(parsed[1].title, results[1].title);
+        assert_eq!(parsed[0].artists[0].name, "Test Artist");
+        assert_eq!(parsed[0].isrc, results[0].isrc);
+
+        let parsed: SearchResults = serde_json::from_str(r#"{"tracks":[]}"#).unwrap();
+        assert!(!parsed.cache_hit);
+
+        let metadata = Metadata {
+            title: "Title".to_owned(),
+            genre: Some("Rock".to_owned()),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&metadata).unwrap();
+        assert_eq!(serde_json::from_str::<Metadata>(&json).unwrap(), metadata);
+        assert_eq!(
+            serde_json::from_str::<Metadata>(r#"{"artist":"Artist"}"#)
+                .unwrap()
+                .artist,
+            "Artist"
+        );
+    }
+
+    #[test]
+    fn search_results_iter() {
+        let mut results = SearchResults::example(2);
+        assert_eq!(results[1].title, "Test Track 2");
+        assert_eq!((&results).into_iter().count(), 2);
+
+        results.extend(SearchResults::example(3));
+        let titles = results
+            .into_iter()
+            .map(|t| t.title)
+            .filter(|t| t.ends_with('1'))
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Test Track 1", "Test Track 1"]);
+
+        let results = SearchResults::from(vec![crate::Track::example()]);
+        assert_eq!(results.tracks.len(), 1);
+    }
+
+    #[test]
+    fn search_results_add() {
+        let mut results = SearchResults::default() + SearchResults::example(2);
+        results += SearchResults::example(3);
+        assert_eq!(results.tracks.len(), 5);
+        assert_eq!(results.total, Some(5));
+        assert!(!results.cache_hit);
+
+        let folded = [SearchResults::example(1), SearchResults::example(2)]
+            .into_iter()
+            .fold(SearchResults::default(), |acc, r| acc + r);
+        assert_eq!((folded.tracks.len(), folded.total), (3, Some(3)));
+        let folded = [SearchResults::example(1), SearchResults::from(vec![])]
+            .into_iter()
+            .fold(SearchResults::default(), |acc, r| acc + r);
+        assert_eq!(folded.total, None);
+
+        results.tracks.push(Track {
+            isrc: Some("gbabc2500002".to_owned()),
+            ..Track::example()
+        });
+        results.tracks.push(Track {
+            isrc: None,
+            ..Track::example()
+        });
+        results.deduplicate_by_isrc();
+        let titles = results.into_iter().map(|t| t.title).collect::<Vec<_>>();
+        assert_eq!(
+            titles,
+            ["Test Track 1", "Test Track 2", "Test Track 3", "Test Track"]
+        );
+    }
+
+    #[tokio::test]
+    async fn save_audio_stream_streaming_test() {
+        let path = std::env::temp_dir().join("fruityger_streaming_test.flac");
+        let progress =
+            save_audio_stream_streaming(audio_stream(vec![b"fruit", b"yger", b"!"]), &path)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+        assert_eq!(
+            progress
+                .iter()
+                .map(|p| (p.bytes_downloaded, p.chunk_size))
+                .collect::<Vec<_>>(),
+            vec![(5, 5), (9, 4), (10, 1)]
+        );
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"fruityger!");
+    }
 
     #[tokio::test]
     async fn remux_test() {
         remux(
             Path::new("/tmp"),
             Path::new(&std::env::var("AUDIO_PATH").unwrap_or("/tmp/audio.flac".to_owned())),
-            Some(Path::new(
-                &std::env::var("AUDIO_PATH").unwrap_or("/tmp/cover.jpg".to_owned()),
-            )),
+            vec![(
+                PathBuf::from(std::env::var("COVER_PATH").unwrap_or("/tmp/cover.jpg".to_owned())),
+                CoverArtType::FrontCover,
+            )],
             AudioFormat::Flac,
             "remux_test",
             Metadata {
@@ -235,4 +1035,71 @@ mod test {
         )
         .unwrap();
     }
+
+    #[test]
+    fn remux_to_memory_test() {
+        let audio_data =
+            std::fs::read(std::env::var("AUDIO_PATH").unwrap_or("/tmp/audio.flac".to_owned()))
+                .unwrap();
+        let cover_data =
+            std::fs::read(std::env::var("COVER_PATH").unwrap_or("/tmp/cover.jpg".to_owned()))
+                .unwrap();
+        let data = remux_to_memory(
+            audio_data.into(),
+            Some(cover_data.into()),
+            AudioFormat::Flac,
+            Metadata {
+                title: "remux test".to_owned(),
+                artist: "fruityger".to_owned(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(data.starts_with(b"fLaC"));
+    }
+
+    #[test]
+    fn wav_remux_test() {
+        // One second of 8 kHz 16-bit mono silence
+        let (sample_rate, data_size) = (8000u32, 16000u32);
+        let mut wav = b"RIFF".to_vec();
+        wav.extend((36 + data_size).to_le_bytes());
+        wav.extend(b"WAVEfmt ");
+        wav.extend(16u32.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(sample_rate.to_le_bytes());
+        wav.extend((sample_rate * 2).to_le_bytes());
+        wav.extend(2u16.to_le_bytes());
+        wav.extend(16u16.to_le_bytes());
+        wav.extend(b"data");
+        wav.extend(data_size.to_le_bytes());
+        wav.resize(wav.len() + data_size as usize, 0);
+        let input_path = std::env::temp_dir().join("fruityger_wav_input.wav");
+        std::fs::write(&input_path, wav).unwrap();
+
+        let (output_path, warnings) = remux(
+            &std::env::temp_dir(),
+            &input_path,
+            vec![(PathBuf::from("/tmp/cover.jpg"), CoverArtType::FrontCover)],
+            AudioFormat::Wav,
+            "fruityger_wav_output",
+            Metadata::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            warnings,
+            [RemuxWarning::CoverTypeNotSupported(
+                CoverArtType::FrontCover
+            )]
+        );
+        assert_eq!(
+            AudioFormat::from_probe(&output_path).unwrap(),
+            AudioFormat::Wav
+        );
+        assert_eq!(
+            ffmpeg_next::format::input(&output_path).unwrap().duration(),
+            ffmpeg_next::format::input(&input_path).unwrap().duration()
+        );
+    }
 }
@@ -0,0 +1,140 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::error::Error;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LyricsResult {
+    pub plain: String,
+    pub synchronized: Option<LrcFile>,
+    pub copyright: Option<String>,
+}
+
+impl LyricsResult {
+    /// Builds a result from plain lyrics and optional LRC text, lyrics with
+    /// malformed LRC still keep their plain text
+    pub fn new(plain: String, lrc: Option<&str>) -> Self {
+        Self {
+            plain,
+            synchronized: lrc.and_then(|lrc| LrcFile::parse(lrc).ok()),
+            copyright: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LrcLine {
+    pub timestamp_ms: u64,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct LrcFile {
+    pub lines: Vec<LrcLine>,
+}
+
+impl LrcFile {
+    pub fn parse(lrc: &str) -> Result<Self, Error> {
+        let mut lines = vec![];
+        for line in lrc.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let mut rest = line;
+            let mut timestamps = vec![];
+            while let Some(tag) = rest.strip_prefix('[') {
+                let (tag, after) = tag
+                    .split_once(']')
+                    .ok_or_else(|| Error::LrcParseError(format!("unclosed tag in '{}'", line)))?;
+                rest = after;
+                // ID tags like [ar:Artist] start with a letter and carry no timing
+                if tag.starts_with(|c: char| c.is_ascii_digit()) {
+                    timestamps.push(Self::parse_timestamp(tag)?);
+                }
+            }
+            if timestamps.is_empty() && rest.len() == line.len() {
+                return Err(Error::LrcParseError(format!(
+                    "line without timestamp '{}'",
+                    line
+                )));
+            }
+            for timestamp_ms in timestamps {
+                lines.push(LrcLine {
+                    timestamp_ms,
+                    text: rest.trim().to_owned(),
+                });
+            }
+        }
+        lines.sort_by_key(|l| l.timestamp_ms);
+        Ok(Self { lines })
+    }
+
+    fn parse_timestamp(tag: &str) -> Result<u64, Error> {
+        let invalid = || Error::LrcParseError(format!("invalid timestamp '{}'", tag));
+        let (minutes, seconds) = tag.split_once(':').ok_or_else(invalid)?;
+        let minutes = minutes.parse::<u64>().map_err(|_| invalid())?;
+        let seconds = seconds.parse::<f64>().map_err(|_| invalid())?;
+        if !(0.0..60.0).contains(&seconds) {
+            return Err(invalid());
+        }
+        Ok(minutes * 60_000 + (seconds * 1000.0).round() as u64)
+    }
+}
+
+impl fmt::Display for LrcFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(
+                f,
+                "[{:02}:{:02}.{:02}]{}",
+                line.timestamp_ms / 60_000,
+                line.timestamp_ms / 1000 % 60,
+                line.timestamp_ms % 1000 / 10,
+                line.text
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lyrics::{LrcFile, LrcLine, LyricsResult};
+
+    #[test]
+    fn lrc_parse() {
+        let lrc =
+            LrcFile::parse("[ar:Artist]\n[00:12.50]First\n\n[01:02.00][00:05.10]Second\n").unwrap();
+        assert_eq!(
+            lrc.lines,
+            [
+                LrcLine {
+                    timestamp_ms: 5100,
+                    text: "Second".to_owned()
+                },
+                LrcLine {
+                    timestamp_ms: 12500,
+                    text: "First".to_owned()
+                },
+                LrcLine {
+                    timestamp_ms: 62000,
+                    text: "Second".to_owned()
+                },
+            ]
+        );
+        assert_eq!(LrcFile::parse(&lrc.to_string()).unwrap(), lrc);
+        assert!(LrcFile::parse("[00:99.00]Bad").is_err());
+        assert!(LrcFile::parse("no timestamp").is_err());
+    }
+
+    #[test]
+    fn lyrics_result() {
+        let lyrics = LyricsResult::new("First".to_owned(), Some("[00:01.00]First"));
+        assert_eq!(lyrics.synchronized.unwrap().lines.len(), 1);
+
+        let lyrics = LyricsResult::new("First".to_owned(), Some("First"));
+        assert_eq!(lyrics.plain, "First");
+        assert!(lyrics.synchronized.is_none());
+    }
+}
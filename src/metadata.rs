@@ -0,0 +1,485 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::{collections::BTreeMap, path::Path};
+
+use ffmpeg_next::Dictionary;
+use id3::TagLike;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, template::FileNameTemplate};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metadata {
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub artist: String,
+    pub comment: Option<String>,
+    pub composer: Option<String>,
+    pub copyright: Option<String>,
+    pub creation_time: Option<String>,
+    pub date: Option<String>,
+    pub disc: Option<String>,
+    pub genre: Option<String>,
+    pub isrc: Option<String>,
+    pub language: Option<String>,
+    /// Unsynchronized lyrics
+    pub lyrics: Option<String>,
+    pub performer: Option<String>,
+    pub publisher: Option<String>,
+    pub title: String,
+    pub track: Option<String>,
+    /// Fields without a dedicated member, set through [`Metadata::set_from_str`]
+    pub custom_fields: BTreeMap<String, String>,
+}
+
+macro_rules! ffmpeg_keys {
+    ($($field:ident),* $(,)?) => {
+        impl Metadata {
+            /// Key/value pairs using FFmpeg's generic metadata keys
+            pub fn to_ffmpeg_entries(&self) -> Vec<(&'static str, String)> {
+                let mut entries = vec![
+                    ("title", self.title.clone()),
+                    ("artist", self.artist.clone()),
+                ];
+                $(
+                    if let Some(v) = &self.$field {
+                        entries.push((stringify!($field), v.clone()));
+                    }
+                )*
+                entries
+            }
+
+            pub fn from_ffmpeg_entries<'a>(
+                entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+            ) -> Metadata {
+                let mut metadata = Metadata::default();
+                // Demuxers keep the case used by the file, e.g. TITLE in FLAC
+                for (key, value) in entries {
+                    match key.to_ascii_lowercase().as_str() {
+                        "title" => metadata.title = value.to_owned(),
+                        "artist" => metadata.artist = value.to_owned(),
+                        $(stringify!($field) => metadata.$field = Some(value.to_owned()),)*
+                        _ => {}
+                    }
+                }
+                metadata
+            }
+
+            /// Sets a field by name. Known fields are `title`, `artist`, `album`,
+            /// `album_artist`, `comment`, `composer`, `copyright`, `creation_time`,
+            /// `date`, `disc`, `genre`, `isrc`, `language`, `lyrics`, `performer`,
+            /// `publisher` and `track`, any other key goes into `custom_fields`.
+            pub fn set_from_str(&mut self, key: &str, value: &str) -> Result<(), Error> {
+                match key {
+                    "" => return Err(Error::InvalidMetadataKey(key.to_owned())),
+                    "title" => self.title = value.to_owned(),
+                    "artist" => self.artist = value.to_owned(),
+                    $(stringify!($field) => self.$field = Some(value.to_owned()),)*
+                    _ => {
+                        self.custom_fields.insert(key.to_owned(), value.to_owned());
+                    }
+                }
+                Ok(())
+            }
+
+            /// Reads a field by name, see [`Metadata::set_from_str`] for the known fields
+            pub fn get_as_str(&self, key: &str) -> Option<&str> {
+                match key {
+                    "title" => Some(&self.title),
+                    "artist" => Some(&self.artist),
+                    $(stringify!($field) => self.$field.as_deref(),)*
+                    _ => self.custom_fields.get(key).map(String::as_str),
+                }
+            }
+        }
+    };
+}
+
+ffmpeg_keys!(
+    album,
+    album_artist,
+    comment,
+    composer,
+    copyright,
+    creation_time,
+    date,
+    disc,
+    genre,
+    isrc,
+    language,
+    lyrics,
+    performer,
+    publisher,
+    track,
+);
+
+impl Metadata {
+    pub fn to_ffmpeg_dict(&self) -> Dictionary<'static> {
+        let mut dict = Dictionary::new();
+        for (key, value) in self.to_ffmpeg_entries() {
+            dict.set(key, &value);
+        }
+        dict
+    }
+
+    pub fn from_ffmpeg_dict(dict: &Dictionary) -> Metadata {
+        Self::from_ffmpeg_entries(dict.iter())
+    }
+}
+
+impl Metadata {
+    /// Year of `date`, which may be `YYYY`, `YYYY-MM` or `YYYY-MM-DD`
+    pub fn year(&self) -> Option<u32> {
+        let year = self.date.as_deref()?.get(..4)?;
+        if !year.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        year.parse().ok()
+    }
+
+    pub fn month(&self) -> Option<u8> {
+        self.date_component(1).filter(|m| (1..=12).contains(m))
+    }
+
+    pub fn day(&self) -> Option<u8> {
+        self.date_component(2).filter(|d| (1..=31).contains(d))
+    }
+
+    fn date_component(&self, index: usize) -> Option<u8> {
+        self.year()?;
+        let component = self.date.as_deref()?.split('-').nth(index)?;
+        if component.len() != 2 || !component.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        component.parse().ok()
+    }
+
+    /// Reads the fields present in `template` from a file name, the extension
+    /// is ignored. Returns `None` if the name does not follow the template.
+    pub fn infer_from_filename(filename: &str, template: &FileNameTemplate) -> Option<Metadata> {
+        let stem = Path::new(filename).file_stem()?.to_str()?;
+        let mut metadata = Metadata::default();
+        for (field, value) in template.captures(stem)? {
+            let value = value.to_owned();
+            match field {
+                "album" => metadata.album = Some(value),
+                "album_artist" => metadata.album_artist = Some(value),
+                "artist" => metadata.artist = value,
+                "date" => metadata.date = Some(value),
+                "disc_number" => metadata.disc = Some(value),
+                "genre" => metadata.genre = Some(value),
+                "title" => metadata.title = value,
+                "track_number" => metadata.track = Some(value),
+                "year" if metadata.date.is_none() => metadata.date = Some(value),
+                _ => {}
+            }
+        }
+        Some(metadata)
+    }
+}
+
+macro_rules! vorbis_comments {
+    ($(($key:literal, $field:ident)),* $(,)?) => {
+        impl Metadata {
+            pub fn to_vorbis_comment_map(&self) -> Vec<(String, String)> {
+                let mut comments = vec![
+                    ("TITLE".to_owned(), self.title.clone()),
+                    ("ARTIST".to_owned(), self.artist.clone()),
+                ];
+                $(
+                    if let Some(v) = &self.$field {
+                        comments.push(($key.to_owned(), v.clone()));
+                    }
+                )*
+                comments
+            }
+
+            pub fn from_vorbis_comment_map(comments: &[(String, String)]) -> Metadata {
+                let mut metadata = Metadata::default();
+                // Keys are case-insensitive and may repeat, the first value wins
+                for (key, value) in comments {
+                    match key.to_ascii_uppercase().as_str() {
+                        "TITLE" if metadata.title.is_empty() => metadata.title = value.clone(),
+                        "ARTIST" if metadata.artist.is_empty() => metadata.artist = value.clone(),
+                        $(
+                            $key if metadata.$field.is_none() => {
+                                metadata.$field = Some(value.clone())
+                            }
+                        )*
+                        _ => {}
+                    }
+                }
+                metadata
+            }
+        }
+    };
+}
+
+vorbis_comments!(
+    ("ALBUM", album),
+    ("ALBUMARTIST", album_artist),
+    ("COMMENT", comment),
+    ("COMPOSER", composer),
+    ("COPYRIGHT", copyright),
+    ("DATE", date),
+    ("DISCNUMBER", disc),
+    ("GENRE", genre),
+    ("ISRC", isrc),
+    ("LANGUAGE", language),
+    ("LYRICS", lyrics),
+    ("PERFORMER", performer),
+    ("ORGANIZATION", publisher),
+    ("TRACKNUMBER", track),
+);
+
+macro_rules! id3_frames {
+    ($(($id:literal, $field:ident)),* $(,)?) => {
+        impl Metadata {
+            pub fn to_id3_tag(&self) -> id3::Tag {
+                let mut tag = id3::Tag::with_version(id3::Version::Id3v24);
+                tag.set_text("TIT2", &self.title);
+                tag.set_text("TPE1", &self.artist);
+                $(
+                    if let Some(v) = &self.$field {
+                        tag.set_text($id, v);
+                    }
+                )*
+                if let Some(lyrics) = &self.lyrics {
+                    tag.add_frame(id3::frame::Lyrics {
+                        lang: "und".to_owned(),
+                        description: String::new(),
+                        text: lyrics.clone(),
+                    });
+                }
+                tag
+            }
+
+            pub fn from_id3_tag(tag: &id3::Tag) -> Metadata {
+                let text = |id| {
+                    tag.get(id)
+                        .and_then(|frame| frame.content().text())
+                        .map(str::to_owned)
+                };
+                Metadata {
+                    title: text("TIT2").unwrap_or_default(),
+                    artist: text("TPE1").unwrap_or_default(),
+                    $($field: text($id),)*
+                    lyrics: tag.lyrics().next().map(|l| l.text.clone()),
+                    ..Default::default()
+                }
+            }
+        }
+    };
+}
+
+id3_frames!(
+    ("TALB", album),
+    ("TRCK", track),
+    ("TPOS", disc),
+    ("TDRC", date),
+    ("TCON", genre),
+    ("TSRC", isrc),
+);
+
+#[cfg(test)]
+mod test {
+    use crate::{Metadata, template::FileNameTemplate};
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn infer_from_filename() {
+        let template = FileNameTemplate::new("{track_number} - {artist} - {title}").unwrap();
+        assert_eq!(
+            Metadata::infer_from_filename("01 - Artist - Title.flac", &template),
+            Some(Metadata {
+                artist: "Artist".to_owned(),
+                title: "Title".to_owned(),
+                track: Some("01".to_owned()),
+                ..Default::default()
+            })
+        );
+        assert_eq!(Metadata::infer_from_filename("Title.flac", &template), None);
+    }
+
+    #[test]
+    fn set_from_str() {
+        let mut metadata = Metadata::default();
+        metadata.set_from_str("title", "My Song").unwrap();
+        metadata.set_from_str("album", "My Album").unwrap();
+        metadata.set_from_str("mood", "calm").unwrap();
+        assert_eq!(metadata.title, "My Song");
+        assert_eq!(metadata.album.as_deref(), Some("My Album"));
+        assert_eq!(metadata.custom_fields["mood"], "calm");
+        assert_eq!(metadata.get_as_str("title"), Some("My Song"));
+        assert_eq!(metadata.get_as_str("album"), Some("My Album"));
+        assert_eq!(metadata.get_as_str("mood"), Some("calm"));
+        assert_eq!(metadata.get_as_str("genre"), None);
+        assert!(metadata.set_from_str("", "value").is_err());
+    }
+
+    #[test]
+    fn date_components() {
+        let date = |date: &str| Metadata {
+            date: Some(date.to_owned()),
+            ..Default::default()
+        };
+        let full = date("2023-04-15");
+        assert_eq!(
+            (full.year(), full.month(), full.day()),
+            (Some(2023), Some(4), Some(15))
+        );
+        let month = date("2023-04");
+        assert_eq!(
+            (month.year(), month.month(), month.day()),
+            (Some(2023), Some(4), None)
+        );
+        assert_eq!(date("2023").year(), Some(2023));
+        assert_eq!(date("2023").month(), None);
+        assert_eq!(date("20x3-04-15").year(), None);
+        assert_eq!(date("20x3-04-15").month(), None);
+        assert_eq!(date("2023-13-01").month(), None);
+        assert_eq!(date("2023-4-1").day(), None);
+        assert_eq!(date("").year(), None);
+        assert_eq!(Metadata::default().year(), None);
+    }
+
+    #[test]
+    fn ffmpeg_entries() {
+        let metadata = Metadata {
+            title: "title".to_owned(),
+            artist: "artist".to_owned(),
+            album_artist: Some("album artist".to_owned()),
+            track: Some("1".to_owned()),
+            ..Default::default()
+        };
+        let entries = metadata
+            .to_ffmpeg_entries()
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        assert_eq!(entries["album_artist"], "album artist");
+        assert!(!entries.contains_key("album"));
+        assert_eq!(
+            Metadata::from_ffmpeg_entries(entries.iter().map(|(k, v)| (*k, v.as_str()))),
+            metadata
+        );
+
+        let entries = HashMap::from([("TITLE", "title"), ("Track", "2")]);
+        let metadata = Metadata::from_ffmpeg_entries(entries);
+        assert_eq!(metadata.title, "title");
+        assert_eq!(metadata.track.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn ffmpeg_dict() {
+        let metadata = Metadata {
+            title: "title".to_owned(),
+            artist: "artist".to_owned(),
+            album: Some("album".to_owned()),
+            date: Some("2025".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Metadata::from_ffmpeg_dict(&metadata.to_ffmpeg_dict()),
+            metadata
+        );
+    }
+
+    #[test]
+    fn metadata_eq() {
+        let metadata = Metadata {
+            title: "title".to_owned(),
+            artist: "artist".to_owned(),
+            album: Some("album".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(metadata, metadata.clone());
+
+        let other = Metadata {
+            album: Some("other album".to_owned()),
+            ..metadata.clone()
+        };
+        assert_ne!(metadata, other);
+
+        let set = HashSet::from([metadata.clone(), metadata.clone(), other]);
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn vorbis_comment_map() {
+        let metadata = Metadata {
+            album: Some("album".to_owned()),
+            album_artist: Some("album artist".to_owned()),
+            artist: "artist".to_owned(),
+            composer: Some("composer".to_owned()),
+            copyright: Some("copyright".to_owned()),
+            date: Some("2025-01-01".to_owned()),
+            disc: Some("1".to_owned()),
+            genre: Some("genre".to_owned()),
+            isrc: Some("GB-ABC-25-00001".to_owned()),
+            language: Some("eng".to_owned()),
+            performer: Some("performer".to_owned()),
+            publisher: Some("publisher".to_owned()),
+            title: "title".to_owned(),
+            track: Some("2".to_owned()),
+            ..Default::default()
+        };
+        let comments = metadata.to_vorbis_comment_map();
+        let keys = comments.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                "TITLE",
+                "ARTIST",
+                "ALBUM",
+                "ALBUMARTIST",
+                "COMPOSER",
+                "COPYRIGHT",
+                "DATE",
+                "DISCNUMBER",
+                "GENRE",
+                "ISRC",
+                "LANGUAGE",
+                "PERFORMER",
+                "ORGANIZATION",
+                "TRACKNUMBER"
+            ]
+        );
+        assert_eq!(Metadata::from_vorbis_comment_map(&comments), metadata);
+
+        let comments = [
+            ("title".to_owned(), "first".to_owned()),
+            ("TITLE".to_owned(), "second".to_owned()),
+            ("TrackNumber".to_owned(), "3".to_owned()),
+            ("UNKNOWN".to_owned(), "ignored".to_owned()),
+        ];
+        let metadata = Metadata::from_vorbis_comment_map(&comments);
+        assert_eq!(metadata.title, "first");
+        assert_eq!(metadata.track.as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn id3_tag() {
+        let metadata = Metadata {
+            album: Some("album".to_owned()),
+            artist: "artist".to_owned(),
+            date: Some("2025-01-01".to_owned()),
+            disc: Some("1".to_owned()),
+            genre: Some("genre".to_owned()),
+            isrc: Some("GB-ABC-25-00001".to_owned()),
+            lyrics: Some("first line\nsecond line".to_owned()),
+            title: "title".to_owned(),
+            track: Some("2".to_owned()),
+            ..Default::default()
+        };
+        let path = std::env::temp_dir().join("fruityger_id3_tag_test.mp3");
+        std::fs::write(&path, [0xff, 0xfb, 0x90, 0x00]).unwrap();
+        metadata
+            .to_id3_tag()
+            .write_to_path(&path, id3::Version::Id3v24)
+            .unwrap();
+        let tag = id3::Tag::read_from_path(&path).unwrap();
+        assert_eq!(Metadata::from_id3_tag(&tag), metadata);
+    }
+}
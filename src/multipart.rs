@@ -0,0 +1,336 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use aes::{
+    Aes128,
+    cipher::{BlockDecryptMut, KeyIvInit, block_padding::Pkcs7},
+};
+use bytes::Bytes;
+use reqwest::Client;
+use url::Url;
+
+use crate::{
+    AudioStream, Metadata,
+    error::Error,
+    format::AudioFormat,
+    remux_to_memory,
+    retry::{RetryPolicy, send_with_retry},
+    util::check_status,
+};
+
+type Aes128CbcDec = cbc::Decryptor<Aes128>;
+
+#[derive(Debug, PartialEq)]
+struct Key {
+    uri: Url,
+    iv: Option<[u8; 16]>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Segment {
+    url: Url,
+    sequence: u64,
+    key: Option<Key>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Variant {
+    url: Url,
+    bandwidth: u64,
+    codecs: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Playlist {
+    variants: Vec<Variant>,
+    map: Option<Url>,
+    segments: Vec<Segment>,
+}
+
+fn invalid(message: &str) -> Error {
+    Error::ServiceError(format!("invalid hls playlist: {}", message))
+}
+
+// Splits `KEY=VALUE,KEY="quoted, value"` attribute lists
+fn parse_attributes(list: &str) -> Vec<(&str, &str)> {
+    let mut attributes = vec![];
+    let mut rest = list;
+    while let Some((key, after)) = rest.split_once('=') {
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let (value, after) = quoted.split_once('"').unwrap_or((quoted, ""));
+                (value, after.strip_prefix(',').unwrap_or(after))
+            }
+            None => after.split_once(',').unwrap_or((after, "")),
+        };
+        attributes.push((key.trim(), value));
+        rest = after;
+    }
+    attributes
+}
+
+fn parse_iv(iv: &str) -> Result<[u8; 16], Error> {
+    let hex = iv
+        .strip_prefix("0x")
+        .or_else(|| iv.strip_prefix("0X"))
+        .filter(|h| h.len() == 32)
+        .ok_or_else(|| invalid("bad key iv"))?;
+    let mut bytes = [0; 16];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte =
+            u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid("bad key iv"))?;
+    }
+    Ok(bytes)
+}
+
+fn parse_playlist(base: &Url, text: &str) -> Result<Playlist, Error> {
+    let join = |uri: &str| {
+        base.join(uri).map_err(|source| Error::UrlParseError {
+            url: uri.to_owned(),
+            source,
+        })
+    };
+
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    if lines.next() != Some("#EXTM3U") {
+        return Err(invalid("missing #EXTM3U header"));
+    }
+
+    let mut playlist = Playlist::default();
+    let mut sequence = 0;
+    let mut key: Option<(Url, Option<[u8; 16]>)> = None;
+    let mut variant: Option<(u64, Option<String>)> = None;
+    for line in lines {
+        if let Some(value) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+            sequence = value.parse().map_err(|_| invalid("bad media sequence"))?;
+        } else if let Some(attributes) = line.strip_prefix("#EXT-X-KEY:") {
+            let attributes = parse_attributes(attributes);
+            let get = |name| attributes.iter().find(|(k, _)| *k == name).map(|(_, v)| *v);
+            key = match get("METHOD") {
+                Some("NONE") => None,
+                Some("AES-128") => Some((
+                    join(get("URI").ok_or_else(|| invalid("key without uri"))?)?,
+                    get("IV").map(parse_iv).transpose()?,
+                )),
+                _ => return Err(Error::UnsupportedFormatError),
+            };
+        } else if let Some(attributes) = line.strip_prefix("#EXT-X-MAP:") {
+            let attributes = parse_attributes(attributes);
+            let uri = attributes
+                .iter()
+                .find(|(k, _)| *k == "URI")
+                .ok_or_else(|| invalid("map without uri"))?
+                .1;
+            playlist.map = Some(join(uri)?);
+        } else if let Some(attributes) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            let attributes = parse_attributes(attributes);
+            let get = |name| attributes.iter().find(|(k, _)| *k == name).map(|(_, v)| *v);
+            variant = Some((
+                get("BANDWIDTH").and_then(|b| b.parse().ok()).unwrap_or(0),
+                get("CODECS").map(str::to_owned),
+            ));
+        } else if line.starts_with('#') {
+            continue;
+        } else if let Some((bandwidth, codecs)) = variant.take() {
+            playlist.variants.push(Variant {
+                url: join(line)?,
+                bandwidth,
+                codecs,
+            });
+        } else {
+            playlist.segments.push(Segment {
+                url: join(line)?,
+                sequence,
+                key: key.as_ref().map(|(uri, iv)| Key {
+                    uri: uri.clone(),
+                    iv: *iv,
+                }),
+            });
+            sequence += 1;
+        }
+    }
+    Ok(playlist)
+}
+
+async fn fetch(client: &Client, retry: &RetryPolicy, url: &Url) -> Result<Bytes, Error> {
+    Ok(check_status(
+        send_with_retry(retry, client.get(url.clone())).await?,
+        "hls",
+    )?
+    .bytes()
+    .await?)
+}
+
+async fn fetch_playlist(
+    client: &Client,
+    retry: &RetryPolicy,
+    url: &Url,
+) -> Result<Playlist, Error> {
+    parse_playlist(
+        url,
+        &String::from_utf8_lossy(&fetch(client, retry, url).await?),
+    )
+}
+
+fn decrypt(data: &[u8], key: &[u8; 16], iv: &[u8; 16]) -> Result<Vec<u8>, Error> {
+    Aes128CbcDec::new(key.into(), iv.into())
+        .decrypt_padded_vec_mut::<Pkcs7>(data)
+        .map_err(|_| invalid("segment decryption failed"))
+}
+
+/// Downloads every segment of an HLS stream into memory, decrypting AES-128
+/// segments. MPEG-TS segments are remuxed so the result holds bare audio.
+/// Every playlist, segment and key request is retried according to `retry`.
+pub async fn download_hls_stream(
+    manifest_url: &str,
+    client: &Client,
+    retry: &RetryPolicy,
+) -> Result<AudioStream, Error> {
+    let mut url = Url::parse(manifest_url).map_err(|source| Error::UrlParseError {
+        url: manifest_url.to_owned(),
+        source,
+    })?;
+    let mut playlist = fetch_playlist(client, retry, &url).await?;
+    let mut codecs = None;
+    if let Some(variant) = playlist.variants.iter().max_by_key(|v| v.bandwidth) {
+        url = variant.url.clone();
+        codecs = variant.codecs.clone();
+        playlist = fetch_playlist(client, retry, &url).await?;
+    }
+    let first = playlist
+        .segments
+        .first()
+        .ok_or_else(|| invalid("no segments"))?;
+    let extension = first
+        .url
+        .path()
+        .rsplit_once('.')
+        .map(|(_, e)| e.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let mut data = match &playlist.map {
+        Some(map) => fetch(client, retry, map).await?.to_vec(),
+        None => vec![],
+    };
+    let mut keys: Vec<(Url, [u8; 16])> = vec![];
+    for segment in &playlist.segments {
+        let bytes = fetch(client, retry, &segment.url).await?;
+        let Some(key) = &segment.key else {
+            data.extend_from_slice(&bytes);
+            continue;
+        };
+        let secret = match keys.iter().find(|(uri, _)| *uri == key.uri) {
+            Some((_, secret)) => *secret,
+            None => {
+                let secret: [u8; 16] = fetch(client, retry, &key.uri)
+                    .await?
+                    .as_ref()
+                    .try_into()
+                    .map_err(|_| invalid("key is not 16 bytes"))?;
+                keys.push((key.uri.clone(), secret));
+                secret
+            }
+        };
+        // Without an explicit IV the media sequence number is used
+        let iv = key
+            .iv
+            .unwrap_or_else(|| (segment.sequence as u128).to_be_bytes());
+        data.extend(decrypt(&bytes, &secret, &iv)?);
+    }
+
+    let is_flac = codecs.is_some_and(|c| c.to_ascii_lowercase().contains("flac"));
+    let (data, format) = match extension.as_str() {
+        "ts" | "aac" => {
+            let format = if is_flac {
+                AudioFormat::FlacMp4
+            } else {
+                AudioFormat::Aac(0)
+            };
+            let data = remux_to_memory(data.into(), None, format, Metadata::default())?;
+            (data, format)
+        }
+        "mp4" | "m4s" | "m4a" if is_flac => (data.into(), AudioFormat::FlacMp4),
+        "mp4" | "m4s" | "m4a" => (data.into(), AudioFormat::Aac(0)),
+        extension => (
+            data.into(),
+            AudioFormat::try_from(format!(".{}", extension).as_str())?,
+        ),
+    };
+    Ok(AudioStream {
+        response: http::Response::new(reqwest::Body::from(data)).into(),
+        format,
+        md5: None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use aes::{
+        Aes128,
+        cipher::{BlockEncryptMut, KeyIvInit, block_padding::Pkcs7},
+    };
+    use url::Url;
+
+    use crate::multipart::{Key, Segment, decrypt, parse_playlist};
+
+    #[test]
+    fn playlist() {
+        let base = Url::parse("https://cdn.example/audio/index.m3u8").unwrap();
+        let playlist = parse_playlist(
+            &base,
+            "#EXTM3U\n\
+             #EXT-X-MEDIA-SEQUENCE:7\n\
+             #EXT-X-KEY:METHOD=AES-128,URI=\"key.bin\",IV=0x000102030405060708090a0b0c0d0e0f\n\
+             #EXTINF:10.0,\n\
+             seg7.ts\n\
+             #EXT-X-KEY:METHOD=NONE\n\
+             #EXTINF:10.0,\n\
+             https://other.example/seg8.ts\n",
+        )
+        .unwrap();
+        assert_eq!(
+            playlist.segments,
+            [
+                Segment {
+                    url: Url::parse("https://cdn.example/audio/seg7.ts").unwrap(),
+                    sequence: 7,
+                    key: Some(Key {
+                        uri: Url::parse("https://cdn.example/audio/key.bin").unwrap(),
+                        iv: Some(core::array::from_fn(|i| i as u8)),
+                    }),
+                },
+                Segment {
+                    url: Url::parse("https://other.example/seg8.ts").unwrap(),
+                    sequence: 8,
+                    key: None,
+                },
+            ]
+        );
+
+        let master = parse_playlist(
+            &base,
+            "#EXTM3U\n\
+             #EXT-X-STREAM-INF:BANDWIDTH=96000,CODECS=\"mp4a.40.2\"\n\
+             low.m3u8\n\
+             #EXT-X-STREAM-INF:BANDWIDTH=1411000,CODECS=\"fLaC\"\n\
+             high.m3u8\n",
+        )
+        .unwrap();
+        assert_eq!(master.variants.len(), 2);
+        assert_eq!(master.variants[1].codecs.as_deref(), Some("fLaC"));
+        assert!(parse_playlist(&base, "seg.ts").is_err());
+    }
+
+    #[test]
+    fn decrypt_segment() {
+        let key = [1; 16];
+        let iv = 7u128.to_be_bytes();
+        let encrypted = cbc::Encryptor::<Aes128>::new(&key.into(), &iv.into())
+            .encrypt_padded_vec_mut::<Pkcs7>(b"fruityger segment");
+        assert_eq!(
+            decrypt(&encrypted, &key, &iv).unwrap(),
+            b"fruityger segment"
+        );
+        assert!(decrypt(&encrypted, &[2; 16], &iv).is_err());
+    }
+}
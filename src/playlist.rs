@@ -0,0 +1,249 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::fmt::Write;
+
+use quick_xml::{Reader, escape::escape, events::Event};
+
+use crate::{Artist, MultiArtistSeparator, Playlist, Track, error::Error};
+
+const XSPF_NAMESPACE: &str = "http://xspf.org/ns/0/";
+
+fn element(xspf: &mut String, indent: usize, name: &str, value: &str) {
+    if !value.is_empty() {
+        let _ = writeln!(
+            xspf,
+            "{:indent$}<{name}>{}</{name}>",
+            "",
+            escape(value),
+            indent = indent
+        );
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, Error> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| Error::PlaylistParseError(format!("invalid {}: '{}'", name, value)))
+}
+
+fn empty_track() -> Track {
+    Track {
+        id: String::new(),
+        url: String::new(),
+        title: String::new(),
+        duration_ms: 0,
+        artists: vec![],
+        cover_url: String::new(),
+        cover_uri_template: None,
+        isrc: None,
+        album_label_id: None,
+        album: None,
+        track_number: None,
+    }
+}
+
+impl Playlist {
+    /// Serializes the playlist as an XSPF 1.0 document. Only `file://` URLs
+    /// are written as track locations, service URLs go into `info` instead.
+    pub fn to_xspf(&self) -> String {
+        let mut xspf = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<playlist version=\"1\" xmlns=\"{}\">\n",
+            XSPF_NAMESPACE
+        );
+        element(&mut xspf, 2, "title", &self.name);
+        element(&mut xspf, 2, "identifier", &self.id);
+        element(&mut xspf, 2, "info", &self.url);
+        element(
+            &mut xspf,
+            2,
+            "image",
+            self.cover_url.as_deref().unwrap_or_default(),
+        );
+        xspf += "  <trackList>\n";
+        for track in &self.tracks {
+            xspf += "    <track>\n";
+            if track.url.starts_with("file://") {
+                element(&mut xspf, 6, "location", &track.url);
+            } else {
+                element(&mut xspf, 6, "info", &track.url);
+            }
+            element(&mut xspf, 6, "identifier", &track.id);
+            element(&mut xspf, 6, "title", &track.title);
+            let creator = track.artist_names_joined(MultiArtistSeparator::default().as_str());
+            element(&mut xspf, 6, "creator", &creator);
+            element(
+                &mut xspf,
+                6,
+                "album",
+                track.album.as_deref().unwrap_or_default(),
+            );
+            if let Some(track_number) = track.track_number {
+                element(&mut xspf, 6, "trackNum", &track_number.to_string());
+            }
+            element(&mut xspf, 6, "duration", &track.duration_ms.to_string());
+            element(&mut xspf, 6, "image", &track.cover_url);
+            xspf += "    </track>\n";
+        }
+        xspf += "  </trackList>\n</playlist>\n";
+        xspf
+    }
+
+    /// Parses an XSPF document, the creator of each track becomes a single artist
+    pub fn from_xspf(content: &str) -> Result<Playlist, Error> {
+        let mut reader = Reader::from_str(content);
+        reader.config_mut().trim_text(true);
+
+        let mut playlist = Playlist {
+            id: String::new(),
+            url: String::new(),
+            name: String::new(),
+            cover_url: None,
+            tracks: vec![],
+        };
+        let mut track: Option<Track> = None;
+        let mut path: Vec<String> = vec![];
+        let mut text = String::new();
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) => {
+                    let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                    if path.is_empty() && name != "playlist" {
+                        return Err(Error::PlaylistParseError(format!(
+                            "expected playlist element, found '{}'",
+                            name
+                        )));
+                    }
+                    if name == "track" && path.last().is_some_and(|p| p == "trackList") {
+                        track = Some(empty_track());
+                    }
+                    path.push(name);
+                    text.clear();
+                }
+                Event::Text(e) => text += &e.unescape()?,
+                Event::CData(e) => text += &String::from_utf8_lossy(&e),
+                Event::End(_) => {
+                    let name = path.pop().unwrap_or_default();
+                    let value = std::mem::take(&mut text);
+                    match (path.last().map(String::as_str), &mut track) {
+                        (Some("trackList"), Some(_)) if name == "track" => {
+                            playlist.tracks.extend(track.take());
+                        }
+                        (Some("track"), Some(track)) => match name.as_str() {
+                            "location" => track.url = value,
+                            "info" if track.url.is_empty() => track.url = value,
+                            "identifier" => track.id = value,
+                            "title" => track.title = value,
+                            "creator" => {
+                                track.artists = vec![Artist {
+                                    id: String::new(),
+                                    name: value,
+                                }]
+                            }
+                            "album" => track.album = Some(value),
+                            "trackNum" => {
+                                track.track_number = Some(parse_number("trackNum", &value)?)
+                            }
+                            "duration" => track.duration_ms = parse_number("duration", &value)?,
+                            "image" => track.cover_url = value,
+                            _ => {}
+                        },
+                        (Some("playlist"), _) => match name.as_str() {
+                            "title" => playlist.name = value,
+                            "identifier" => playlist.id = value,
+                            "info" => playlist.url = value,
+                            "image" => playlist.cover_url = Some(value),
+                            _ => {}
+                        },
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        if !path.is_empty() || content.trim().is_empty() {
+            return Err(Error::PlaylistParseError(
+                "unexpected end of document".to_owned(),
+            ));
+        }
+        Ok(playlist)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Artist, Playlist, Track};
+
+    #[test]
+    fn xspf() {
+        let playlist = Playlist {
+            id: "1".to_owned(),
+            url: "https://example.com/playlist/1".to_owned(),
+            name: "Rock & Roll <3".to_owned(),
+            cover_url: None,
+            tracks: vec![
+                Track {
+                    id: "10".to_owned(),
+                    url: "file:///music/Bj%C3%B6rk.flac".to_owned(),
+                    title: "Jóga".to_owned(),
+                    duration_ms: 305000,
+                    artists: vec![Artist {
+                        id: String::new(),
+                        name: "Björk".to_owned(),
+                    }],
+                    cover_url: "https://example.com/cover.jpg".to_owned(),
+                    cover_uri_template: None,
+                    isrc: None,
+                    album_label_id: None,
+                    album: Some("Homogenic".to_owned()),
+                    track_number: Some(3),
+                },
+                Track {
+                    id: "11".to_owned(),
+                    url: "https://example.com/track/11".to_owned(),
+                    title: "\"Quoted\"".to_owned(),
+                    duration_ms: 1000,
+                    artists: vec![],
+                    cover_url: String::new(),
+                    cover_uri_template: None,
+                    isrc: None,
+                    album_label_id: None,
+                    album: None,
+                    track_number: None,
+                },
+            ],
+        };
+
+        let xspf = playlist.to_xspf();
+        assert!(xspf.contains("<title>Rock &amp; Roll &lt;3</title>"));
+        assert!(xspf.contains("<location>file:///music/Bj%C3%B6rk.flac</location>"));
+        assert!(xspf.contains("<creator>Björk</creator>"));
+        assert!(xspf.contains("<trackNum>3</trackNum>"));
+        assert!(xspf.contains("<duration>305000</duration>"));
+        assert!(xspf.contains("<info>https://example.com/track/11</info>"));
+
+        let parsed = Playlist::from_xspf(&xspf).unwrap();
+        assert_eq!(parsed.name, playlist.name);
+        assert_eq!(parsed.url, playlist.url);
+        assert_eq!(parsed.tracks.len(), 2);
+        assert_eq!(parsed.tracks[0].title, "Jóga");
+        assert_eq!(parsed.tracks[0].artists[0].name, "Björk");
+        assert_eq!(parsed.tracks[0].url, playlist.tracks[0].url);
+        assert_eq!(parsed.tracks[0].track_number, Some(3));
+        assert_eq!(parsed.tracks[0].duration_ms, 305000);
+        assert_eq!(parsed.tracks[1].title, "\"Quoted\"");
+        assert_eq!(parsed.tracks[1].url, playlist.tracks[1].url);
+        assert!(parsed.tracks[1].artists.is_empty());
+
+        assert!(Playlist::from_xspf("<html></html>").is_err());
+        assert!(Playlist::from_xspf("<playlist><trackList>").is_err());
+        assert!(
+            Playlist::from_xspf(
+                "<playlist><trackList><track><duration>x</duration></track></trackList></playlist>"
+            )
+            .is_err()
+        );
+    }
+}
@@ -1,17 +1,62 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
-use crate::{AudioFormat, AudioStream, Error, SearchResults, const_headers};
+use crate::{
+    Album, AudioFormat, AudioStream, Error, Playlist, SearchResults, Track, const_headers,
+    format::QobuzQualityId,
+    id::{QobuzAlbumId, QobuzTrackId},
+    lyrics::LyricsResult,
+    no_op_progress,
+    retry::{RetryPolicy, send_with_retry},
+    save_with_limit,
+    util::{check_status, join_url, paginate, sanitize_path_component, status_error},
+};
 use chrono::Utc;
+use futures::Stream;
 use md5::{Digest, Md5};
 use reqwest::{Client, Method, RequestBuilder, redirect::Policy};
-use serde::Deserialize;
-use url::Url;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::path::{Path, PathBuf};
+
+const DEFAULT_BASE_URL: &str = "http://www.qobuz.com";
+const PLAYLIST_PAGE_SIZE: usize = 50;
+const PAGE_SIZE: usize = 20;
+// The new releases feed is global, a label without recent releases would
+// otherwise have it paged to the end
+const LABEL_FEED_MAX_PAGES: usize = 10;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PlaylistInfo {
+    pub id: u64,
+    pub name: String,
+    pub tracks_count: u32,
+    pub cover_url: Option<String>,
+    pub is_public: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum GoodieType {
+    PdfBooklet,
+    HighResImage,
+    Other(String),
+}
+
+/// Extra file shipped with an album purchase
+#[derive(Clone, Debug, Serialize)]
+pub struct AlbumGoodie {
+    pub id: u64,
+    pub goodie_type: GoodieType,
+    pub url: String,
+    pub filename: String,
+    /// Zero when the service does not report a size
+    pub file_size_bytes: u64,
+}
 
 #[derive(Clone)]
 pub struct Qobuz {
     client: reqwest::Client,
     config: Config,
+    base_url: String,
 }
 
 #[derive(Clone, Deserialize)]
@@ -19,6 +64,40 @@ pub struct Config {
     token: String,
     app_id: String,
     app_secret: String,
+    #[serde(default)]
+    retry: RetryPolicy,
+}
+
+impl Config {
+    pub fn new(
+        token: impl Into<String>,
+        app_id: impl Into<String>,
+        app_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            token: token.into(),
+            app_id: app_id.into(),
+            app_secret: app_secret.into(),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Retries timeouts and server errors according to `retry`
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Reads the credentials from `FRUITYGER_QOBUZ_TOKEN`, `FRUITYGER_QOBUZ_APP_ID`
+    /// and `FRUITYGER_QOBUZ_APP_SECRET`
+    pub fn from_env() -> Result<Self, Error> {
+        Ok(Self {
+            token: std::env::var("FRUITYGER_QOBUZ_TOKEN")?,
+            app_id: std::env::var("FRUITYGER_QOBUZ_APP_ID")?,
+            app_secret: std::env::var("FRUITYGER_QOBUZ_APP_SECRET")?,
+            retry: RetryPolicy::default(),
+        })
+    }
 }
 
 impl Qobuz {
@@ -37,41 +116,302 @@ impl Qobuz {
                 .build()
                 .unwrap(),
             config,
+            base_url: DEFAULT_BASE_URL.to_owned(),
         }
     }
 
-    fn builder<S: AsRef<str>>(&self, method: Method, url: S) -> RequestBuilder {
-        self.client
+    /// Sends API requests to `base_url` instead of the Qobuz API, e.g. a mock server
+    pub fn with_base_url(config: Config, base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            ..Self::new(config)
+        }
+    }
+
+    pub fn service_name(&self) -> &'static str {
+        "qobuz"
+    }
+
+    pub fn supported_formats(&self) -> Vec<AudioFormat> {
+        vec![AudioFormat::Flac, AudioFormat::Mp3(320)]
+    }
+
+    fn builder<S: AsRef<str>>(&self, method: Method, url: S) -> Result<RequestBuilder, Error> {
+        Ok(self
+            .client
             .request(
                 method,
-                Url::parse("http://www.qobuz.com")
-                    .and_then(|u| u.join(&format!("/api.json/0.2/{}", url.as_ref())))
-                    .unwrap(),
+                join_url(
+                    &self.base_url,
+                    &format!("/api.json/0.2/{}", url.as_ref().trim_start_matches('/')),
+                )?,
             )
             .header("x-user-auth-token", &self.config.token)
-            .query(&[("app_id", &self.config.app_id)])
+            .query(&[("app_id", &self.config.app_id)]))
     }
 
-    pub async fn search(&self, query: &str, page: usize) -> Result<SearchResults, Error> {
-        let response = self
-            .builder(Method::GET, "/catalog/search")
-            .query(&[
+    // Errors come with a JSON body, either alongside an error status or,
+    // for some endpoints, with a 200
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, Error> {
+        let response = send_with_retry(&self.config.retry, request).await?;
+        let status = response.status();
+        if status.is_client_error() || status.is_server_error() {
+            let message = match response.json::<data::ApiResponse<()>>().await {
+                Ok(data::ApiResponse::Err { message, .. }) => message,
+                _ => format!("{} returned {}", self.service_name(), status),
+            };
+            return Err(status_error(status, message));
+        }
+        response.json::<data::ApiResponse<T>>().await?.into()
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T, Error> {
+        self.send(self.builder(Method::GET, url)?.query(query))
+            .await
+    }
+
+    /// `kind` is the result type, `tracks` or `albums`
+    async fn catalog_search(
+        &self,
+        query: &str,
+        kind: &str,
+        page: usize,
+    ) -> Result<data::SearchResponse, Error> {
+        self.get(
+            "/catalog/search",
+            &[
                 ("query", query),
-                ("limit", "20"),
-                ("offset", &(page * 20).to_string()),
-            ])
-            .send()
-            .await?;
-        response
-            .json::<data::ApiResponse<data::SearchResponse>>()
+                ("type", kind),
+                ("limit", &PAGE_SIZE.to_string()),
+                ("offset", &(page * PAGE_SIZE).to_string()),
+            ],
+        )
+        .await
+    }
+
+    pub async fn search(&self, query: &str, page: usize) -> Result<SearchResults, Error> {
+        Ok(self.catalog_search(query, "tracks", page).await?.into())
+    }
+
+    /// Fetches search pages on demand and yields their tracks one by one
+    pub fn search_all<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> impl Stream<Item = Result<Track, Error>> + 'a {
+        paginate(move |page| self.search(query, page))
+    }
+
+    pub async fn search_albums(&self, query: &str, page: usize) -> Result<Vec<Album>, Error> {
+        Ok(self
+            .catalog_search(query, "albums", page)
             .await?
-            .into()
+            .albums
+            .map(|albums| albums.items.into_iter().map(data::Album::into).collect())
+            .unwrap_or_default())
     }
 
-    pub async fn get_stream(&self, id: &str) -> Result<AudioStream, Error> {
+    pub async fn get_label_releases(
+        &self,
+        label_id: &str,
+        page: usize,
+    ) -> Result<Vec<Album>, Error> {
+        Ok(self
+            .get::<data::LabelResponse>(
+                "/label/get",
+                &[
+                    ("label_id", label_id),
+                    ("extra", "albums"),
+                    ("limit", &PAGE_SIZE.to_string()),
+                    ("offset", &(page * PAGE_SIZE).to_string()),
+                ],
+            )
+            .await?
+            .albums
+            .items
+            .into_iter()
+            .map(data::Album::into)
+            .collect())
+    }
+
+    // The label filter is not honored for every feed, so releases are
+    // matched here as well and the feed is paged until `page` is filled or
+    // `LABEL_FEED_MAX_PAGES` were read
+    pub async fn get_label_new_releases(
+        &self,
+        label_id: &str,
+        page: usize,
+    ) -> Result<Vec<Album>, Error> {
+        let mut skip = page * PAGE_SIZE;
+        let mut albums = vec![];
+        let mut offset = 0;
+        for _ in 0..LABEL_FEED_MAX_PAGES {
+            let items = self
+                .get::<data::FeaturedResponse>(
+                    "/catalog/getFeatured",
+                    &[
+                        ("type", "new-releases"),
+                        ("label_id", label_id),
+                        ("limit", &PAGE_SIZE.to_string()),
+                        ("offset", &offset.to_string()),
+                    ],
+                )
+                .await?
+                .albums
+                .items;
+            let count = items.len();
+            for album in items {
+                if album
+                    .label
+                    .as_ref()
+                    .is_none_or(|label| label.id.to_string() != label_id)
+                {
+                    continue;
+                }
+                if skip > 0 {
+                    skip -= 1;
+                    continue;
+                }
+                albums.push(album.into());
+                if albums.len() == PAGE_SIZE {
+                    return Ok(albums);
+                }
+            }
+            if count < PAGE_SIZE {
+                break;
+            }
+            offset += count;
+        }
+        Ok(albums)
+    }
+
+    /// Album with its tracks, `url` is either a link to the album or its ID
+    pub async fn get_album(&self, url: &str) -> Result<Album, Error> {
+        let id = QobuzAlbumId::try_from(url)?;
+        Ok(self
+            .get::<data::AlbumResponse>("/album/get", &[("album_id", id.as_str())])
+            .await?
+            .into())
+    }
+
+    pub async fn get_similar_albums(&self, album_id: &str) -> Result<Vec<Album>, Error> {
+        Ok(self
+            .get::<data::SimilarAlbumsResponse>(
+                "/album/getSimilarAlbums",
+                &[("album_id", album_id)],
+            )
+            .await?
+            .albums
+            .items
+            .into_iter()
+            .map(data::Album::into)
+            .collect())
+    }
+
+    pub async fn get_artist_top_tracks(
+        &self,
+        artist_id: &str,
+        limit: usize,
+    ) -> Result<Vec<Track>, Error> {
+        Ok(self
+            .get::<data::ArtistTracksResponse>(
+                "/artist/get",
+                &[
+                    ("artist_id", artist_id),
+                    ("extra", "tracks"),
+                    ("limit", &limit.to_string()),
+                ],
+            )
+            .await?
+            .tracks
+            .items
+            .into_iter()
+            .map(data::Track::into)
+            .collect())
+    }
+
+    pub async fn get_user_playlists(&self, page: usize) -> Result<Vec<PlaylistInfo>, Error> {
+        Ok(self
+            .get::<data::UserPlaylistsResponse>(
+                "/playlist/getUserPlaylists",
+                &[
+                    ("limit", &PAGE_SIZE.to_string()),
+                    ("offset", &(page * PAGE_SIZE).to_string()),
+                ],
+            )
+            .await?
+            .playlists
+            .items
+            .into_iter()
+            .map(data::Playlist::into)
+            .collect())
+    }
+
+    /// Fetches every page of the playlist's tracks
+    pub async fn get_playlist(&self, playlist_id: u64) -> Result<Playlist, Error> {
+        let playlist_id = playlist_id.to_string();
+        let mut tracks = vec![];
+        loop {
+            let mut page = self
+                .get::<data::Playlist>(
+                    "/playlist/get",
+                    &[
+                        ("playlist_id", &playlist_id),
+                        ("extra", "tracks"),
+                        ("limit", &PLAYLIST_PAGE_SIZE.to_string()),
+                        ("offset", &tracks.len().to_string()),
+                    ],
+                )
+                .await?;
+            let results = page.tracks.take().unwrap_or_default();
+            let done = results.items.is_empty()
+                || results
+                    .total
+                    .is_none_or(|total| tracks.len() + results.items.len() >= total);
+            tracks.extend(results.items.into_iter().map(data::Track::into));
+            if done {
+                let mut playlist = Playlist::from(page);
+                playlist.tracks = tracks;
+                return Ok(playlist);
+            }
+        }
+    }
+
+    pub async fn get_album_goodies(&self, album_id: &str) -> Result<Vec<AlbumGoodie>, Error> {
+        Ok(self
+            .get::<data::GoodiesResponse>("/album/getGoodies", &[("album_id", album_id)])
+            .await?
+            .goodies
+            .into_iter()
+            .map(data::Goodie::into)
+            .collect())
+    }
+
+    /// Downloads the goodie into `dir` under its own file name
+    pub async fn save_goodie(&self, goodie: &AlbumGoodie, dir: &Path) -> Result<PathBuf, Error> {
+        let path = dir.join(sanitize_path_component(&goodie.filename));
+        let response = check_status(
+            send_with_retry(&self.config.retry, self.client.get(&goodie.url)).await?,
+            self.service_name(),
+        )?;
+        save_with_limit(response, &path, None, no_op_progress).await?;
+        Ok(path)
+    }
+
+    pub async fn get_track_lyrics(&self, track_id: &str) -> Result<Option<LyricsResult>, Error> {
+        Ok(self
+            .get::<data::LyricsResponse>("/track/getLyrics", &[("track_id", track_id)])
+            .await?
+            .into())
+    }
+
+    pub async fn get_stream(&self, id: &QobuzTrackId) -> Result<AudioStream, Error> {
         let ts = Utc::now().timestamp();
         let mut query = [
-            ("format_id", "6".to_string()),
+            ("format_id", QobuzQualityId::FLAC_16.0.to_string()),
             ("intent", "stream".to_string()),
             ("sample", "false".to_string()),
             ("track_id", id.to_string()),
@@ -86,55 +426,103 @@ impl Qobuz {
             ));
             format!("{:x}", h.finalize())
         };
-        let response = match self
-            .builder(Method::GET, "/track/getFileUrl")
-            .query(&query)
-            .send()
-            .await?
-            .json::<data::ApiResponse<data::GetFileUrlResponse>>()
-            .await?
-        {
-            data::ApiResponse::Ok(v) => v,
-            data::ApiResponse::Err { message, .. } => {
-                return Err(Error::ServiceError(message));
-            }
-        };
+        let response = self
+            .send::<data::GetFileUrlResponse>(
+                self.builder(Method::GET, "/track/getFileUrl")?
+                    .query(&query),
+            )
+            .await?;
 
         if response.sample {
             return Err(Error::ServiceError("cannot get full song".to_owned()));
         }
 
         let format = match response.mime_type.as_str() {
-            "audio/flac" => AudioFormat::Flac,
+            AudioFormat::FLAC_MIME_TYPE => AudioFormat::Flac,
             _ => return Err(Error::UnsupportedFormatError),
         };
 
         Ok(AudioStream {
-            response: self.client.get(response.url).send().await?,
+            response: check_status(
+                send_with_retry(&self.config.retry, self.client.get(response.url)).await?,
+                self.service_name(),
+            )?,
             format,
+            md5: response.md5,
         })
     }
 }
 
 mod data {
-    use crate::{SearchResults, error::Error};
+    use crate::{error::Error, util::status_error};
+    use reqwest::StatusCode;
     use serde::Deserialize;
 
     #[derive(Debug, Deserialize)]
     #[serde(untagged)]
     pub enum ApiResponse<T> {
+        // Checked first, responses made of optional fields only would
+        // otherwise accept error bodies as well
+        Err {
+            // Never read, only present so that the variant requires
+            // `"status": "error"` to match
+            #[allow(dead_code)]
+            status: ErrorStatus,
+            code: Option<u16>,
+            message: String,
+        },
         Ok(T),
-        Err { message: String },
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum ErrorStatus {
+        Error,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct LyricsResponse {
+        pub lyrics: Option<String>,
+        pub copyright: Option<String>,
     }
 
     #[derive(Debug, Deserialize)]
     pub struct SearchResponse {
+        // Only the requested type is returned
+        #[serde(default)]
         pub tracks: Results<Track>,
+        pub albums: Option<Results<Album>>,
     }
 
     #[derive(Debug, Deserialize)]
     pub struct Results<T> {
         pub items: Vec<T>,
+        pub total: Option<usize>,
+    }
+
+    impl<T> Default for Results<T> {
+        fn default() -> Self {
+            Self {
+                items: vec![],
+                total: None,
+            }
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct UserPlaylistsResponse {
+        pub playlists: Results<Playlist>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Playlist {
+        pub id: u64,
+        pub name: String,
+        pub tracks_count: u32,
+        #[serde(default)]
+        pub images300: Vec<String>,
+        pub is_public: bool,
+        pub tracks: Option<Results<Track>>,
     }
 
     #[derive(Debug, Deserialize)]
@@ -144,6 +532,8 @@ mod data {
         pub duration: usize,
         pub performer: Performer,
         pub album: Album,
+        pub isrc: Option<String>,
+        pub track_number: Option<u32>,
     }
 
     #[derive(Debug, Deserialize)]
@@ -154,7 +544,59 @@ mod data {
 
     #[derive(Debug, Deserialize)]
     pub struct Album {
+        pub id: String,
+        pub title: String,
+        pub artist: Option<Performer>,
+        pub tracks_count: Option<usize>,
+        pub media_count: Option<usize>,
+        pub release_date_original: Option<String>,
         pub image: Image,
+        pub label: Option<Label>,
+        pub maximum_sampling_rate: Option<f64>,
+        pub maximum_bit_depth: Option<u32>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Label {
+        pub id: u64,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct AlbumResponse {
+        #[serde(flatten)]
+        pub album: Album,
+        pub tracks: Option<Results<AlbumTrack>>,
+    }
+
+    // Album listings leave out the album of each track
+    #[derive(Debug, Deserialize)]
+    pub struct AlbumTrack {
+        pub id: u64,
+        pub title: String,
+        pub duration: usize,
+        pub performer: Option<Performer>,
+        pub isrc: Option<String>,
+        pub track_number: Option<u32>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct LabelResponse {
+        pub albums: Results<Album>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct FeaturedResponse {
+        pub albums: Results<Album>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct SimilarAlbumsResponse {
+        pub albums: Results<Album>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct ArtistTracksResponse {
+        pub tracks: Results<Track>,
     }
 
     #[derive(Debug, Deserialize)]
@@ -162,18 +604,93 @@ mod data {
         pub large: String,
     }
 
+    #[derive(Debug, Deserialize)]
+    pub struct GoodiesResponse {
+        #[serde(default)]
+        pub goodies: Vec<Goodie>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Goodie {
+        pub id: u64,
+        pub file_format_id: Option<u32>,
+        pub name: Option<String>,
+        pub url: String,
+        pub original_url: Option<String>,
+        pub file_size: Option<u64>,
+    }
+
+    // Format 21 is the digital booklet
+    const BOOKLET_FORMAT_ID: u32 = 21;
+
+    impl From<Goodie> for crate::qobuz::AlbumGoodie {
+        fn from(value: Goodie) -> Self {
+            use crate::qobuz::GoodieType;
+
+            let url = value.original_url.unwrap_or(value.url);
+            let path_name = url
+                .split(['?', '#'])
+                .next()
+                .and_then(|u| u.rsplit('/').next())
+                .filter(|n| n.contains('.'))
+                .map(str::to_owned);
+            let extension = path_name
+                .as_deref()
+                .and_then(|n| n.rsplit_once('.'))
+                .map(|(_, e)| e.to_ascii_lowercase())
+                .unwrap_or_default();
+            let goodie_type = match extension.as_str() {
+                _ if value.file_format_id == Some(BOOKLET_FORMAT_ID) => GoodieType::PdfBooklet,
+                "pdf" => GoodieType::PdfBooklet,
+                "jpg" | "jpeg" | "png" => GoodieType::HighResImage,
+                _ => GoodieType::Other(value.name.clone().unwrap_or(extension)),
+            };
+            let filename = path_name.unwrap_or_else(|| {
+                let name = value.name.unwrap_or_else(|| value.id.to_string());
+                match goodie_type {
+                    GoodieType::PdfBooklet => format!("{}.pdf", name),
+                    _ => name,
+                }
+            });
+            Self {
+                id: value.id,
+                goodie_type,
+                url,
+                filename,
+                file_size_bytes: value.file_size.unwrap_or(0),
+            }
+        }
+    }
+
     #[derive(Debug, Deserialize)]
     pub struct GetFileUrlResponse {
         pub url: String,
         pub mime_type: String,
         pub sample: bool,
+        pub md5: Option<String>,
     }
 
-    impl From<ApiResponse<SearchResponse>> for Result<SearchResults, Error> {
-        fn from(value: ApiResponse<SearchResponse>) -> Self {
+    impl From<LyricsResponse> for Option<crate::lyrics::LyricsResult> {
+        fn from(value: LyricsResponse) -> Self {
+            let plain = value.lyrics.filter(|l| !l.trim().is_empty())?;
+            Some(crate::lyrics::LyricsResult {
+                plain,
+                synchronized: None,
+                copyright: value.copyright,
+            })
+        }
+    }
+
+    impl<T> From<ApiResponse<T>> for Result<T, Error> {
+        fn from(value: ApiResponse<T>) -> Self {
             match value {
-                ApiResponse::Ok(v) => Ok(v.into()),
-                ApiResponse::Err { message, .. } => Err(Error::ServiceError(message)),
+                ApiResponse::Ok(v) => Ok(v),
+                ApiResponse::Err { code, message, .. } => {
+                    Err(match code.and_then(|c| StatusCode::from_u16(c).ok()) {
+                        Some(status) => status_error(status, message),
+                        None => Error::ServiceError(message),
+                    })
+                }
             }
         }
     }
@@ -182,6 +699,8 @@ mod data {
         fn from(value: SearchResponse) -> Self {
             Self {
                 tracks: value.tracks.items.into_iter().map(Track::into).collect(),
+                total: value.tracks.total,
+                cache_hit: false,
             }
         }
     }
@@ -198,33 +717,232 @@ mod data {
                     name: value.performer.name,
                 }],
                 cover_url: value.album.image.large,
+                cover_uri_template: None,
+                isrc: value.isrc,
+                album_label_id: value.album.label.map(|label| label.id.to_string()),
+                album: Some(value.album.title),
+                track_number: value.track_number,
+            }
+        }
+    }
+
+    impl From<Playlist> for crate::qobuz::PlaylistInfo {
+        fn from(value: Playlist) -> Self {
+            Self {
+                id: value.id,
+                name: value.name,
+                tracks_count: value.tracks_count,
+                cover_url: value.images300.into_iter().next(),
+                is_public: value.is_public,
             }
         }
     }
+
+    impl From<Playlist> for crate::Playlist {
+        fn from(value: Playlist) -> Self {
+            Self {
+                id: value.id.to_string(),
+                url: format!("https://open.qobuz.com/playlist/{}", value.id),
+                name: value.name,
+                cover_url: value.images300.into_iter().next(),
+                tracks: value
+                    .tracks
+                    .map(|t| t.items.into_iter().map(Track::into).collect())
+                    .unwrap_or_default(),
+            }
+        }
+    }
+
+    impl From<Album> for crate::Album {
+        fn from(value: Album) -> Self {
+            Self {
+                url: format!("https://open.qobuz.com/album/{}", value.id),
+                id: value.id,
+                title: value.title,
+                artists: value
+                    .artist
+                    .map(|artist| {
+                        vec![crate::Artist {
+                            id: artist.id.to_string(),
+                            name: artist.name,
+                        }]
+                    })
+                    .unwrap_or_default(),
+                cover_url: value.image.large,
+                release_date: value.release_date_original,
+                track_count: value.tracks_count.unwrap_or_default(),
+                disc_count: value.media_count,
+                max_sampling_rate_khz: value.maximum_sampling_rate,
+                max_bit_depth: value.maximum_bit_depth,
+                tracks: vec![],
+            }
+        }
+    }
+
+    impl From<AlbumResponse> for crate::Album {
+        fn from(value: AlbumResponse) -> Self {
+            let album_label_id = value.album.label.as_ref().map(|l| l.id.to_string());
+            let mut album = crate::Album::from(value.album);
+            album.tracks = value
+                .tracks
+                .unwrap_or_default()
+                .items
+                .into_iter()
+                .map(|track| crate::Track {
+                    id: track.id.to_string(),
+                    url: format!("https://open.qobuz.com/track/{}", track.id),
+                    title: track.title,
+                    duration_ms: track.duration * 1000,
+                    artists: track
+                        .performer
+                        .map(|performer| {
+                            vec![crate::Artist {
+                                id: performer.id.to_string(),
+                                name: performer.name,
+                            }]
+                        })
+                        .unwrap_or_else(|| album.artists.clone()),
+                    cover_url: album.cover_url.clone(),
+                    cover_uri_template: None,
+                    isrc: track.isrc,
+                    album_label_id: album_label_id.clone(),
+                    album: Some(album.title.clone()),
+                    track_number: track.track_number,
+                })
+                .collect();
+            album
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::{
-        qobuz::{Config, Qobuz},
+        error::Error,
+        id::QobuzTrackId,
+        lyrics::LyricsResult,
+        no_op_progress,
+        qobuz::{AlbumGoodie, Config, GoodieType, PlaylistInfo, Qobuz, data},
         save_audio_stream,
     };
     use std::path::Path;
 
+    #[test]
+    fn lyrics_response() {
+        let parse = |json: &str| -> Result<Option<LyricsResult>, Error> {
+            Result::from(serde_json::from_str::<
+                data::ApiResponse<data::LyricsResponse>,
+            >(json)?)
+            .map(Option::from)
+        };
+        let lyrics = parse(r#"{"lyrics":"First","copyright":"Label"}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(lyrics.plain, "First");
+        assert_eq!(lyrics.copyright.as_deref(), Some("Label"));
+        assert!(parse("{}").unwrap().is_none());
+        assert!(parse(r#"{"status":"error","code":404,"message":"Not found"}"#).is_err());
+    }
+
+    #[test]
+    fn playlist_response() {
+        let playlist: data::Playlist = serde_json::from_str(
+            r#"{
+                "id": 1,
+                "name": "Playlist",
+                "tracks_count": 120,
+                "images300": ["https://static.qobuz.com/1.jpg"],
+                "is_public": true,
+                "tracks": {"offset": 0, "limit": 50, "total": 120, "items": []}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(playlist.tracks.as_ref().unwrap().total, Some(120));
+        let info = PlaylistInfo::from(playlist);
+        assert_eq!(
+            info.cover_url.as_deref(),
+            Some("https://static.qobuz.com/1.jpg")
+        );
+        assert!(info.is_public);
+    }
+
+    #[test]
+    fn album_response() {
+        let response: data::AlbumResponse = serde_json::from_str(
+            r#"{
+                "id": "0060254728778",
+                "title": "Discovery",
+                "artist": {"id": 36819, "name": "Daft Punk"},
+                "tracks_count": 2,
+                "media_count": 1,
+                "release_date_original": "2001-03-12",
+                "image": {"large": "https://static.qobuz.com/images/covers/78/87/0060254728778_600.jpg"},
+                "label": {"id": 1153},
+                "maximum_sampling_rate": 44.1,
+                "maximum_bit_depth": 16,
+                "tracks": {"items": [
+                    {"id": 1, "title": "One More Time", "duration": 320, "track_number": 1,
+                     "performer": {"id": 36819, "name": "Daft Punk"}, "isrc": "GBDUW0000053"},
+                    {"id": 2, "title": "Aerodynamic", "duration": 212, "track_number": 2}
+                ], "total": 2}
+            }"#,
+        )
+        .unwrap();
+        let album = crate::Album::from(response);
+        assert_eq!(album.id, "0060254728778");
+        assert_eq!(album.release_date.as_deref(), Some("2001-03-12"));
+        assert_eq!(album.max_sampling_rate_khz, Some(44.1));
+        assert_eq!(album.max_bit_depth, Some(16));
+        assert_eq!(album.tracks.len(), 2);
+        let track = &album.tracks[1];
+        assert_eq!(track.album.as_deref(), Some("Discovery"));
+        assert_eq!(track.artists[0].name, "Daft Punk");
+        assert_eq!(track.cover_url, album.cover_url);
+        assert_eq!(track.album_label_id.as_deref(), Some("1153"));
+        assert_eq!(album.total_duration_ms(), 532_000);
+    }
+
+    #[test]
+    fn goodies_response() {
+        let response: data::GoodiesResponse = serde_json::from_str(
+            r#"{"goodies": [
+                {
+                    "id": 1,
+                    "file_format_id": 21,
+                    "name": "Digital Booklet",
+                    "url": "https://static.qobuz.com/goodies/1/booklet.pdf?token=x",
+                    "file_size": 2048
+                },
+                {"id": 2, "name": "Poster", "url": "https://static.qobuz.com/goodies/2/poster.png"},
+                {"id": 3, "file_format_id": 21, "name": "Booklet", "url": "https://static.qobuz.com/goodies/3"}
+            ]}"#,
+        )
+        .unwrap();
+        let goodies = response
+            .goodies
+            .into_iter()
+            .map(AlbumGoodie::from)
+            .collect::<Vec<_>>();
+        assert_eq!(goodies[0].goodie_type, GoodieType::PdfBooklet);
+        assert_eq!(goodies[0].filename, "booklet.pdf");
+        assert_eq!(goodies[0].file_size_bytes, 2048);
+        assert_eq!(goodies[1].goodie_type, GoodieType::HighResImage);
+        assert_eq!(goodies[1].file_size_bytes, 0);
+        assert_eq!(goodies[2].filename, "Booklet.pdf");
+    }
+
     #[tokio::test]
     async fn all() {
         let query = std::env::var("FRUITYGER_QOBUZ_QUERY").unwrap_or("periphery scarlet".to_string());
-        let client = Qobuz::new(Config {
-            token: std::env::var("FRUITYGER_QOBUZ_TOKEN")
-                .expect("FRUITYGER_QOBUZ_TOKEN is required to test this module"),
-            app_id: std::env::var("FRUITYGER_QOBUZ_APP_ID")
-                .expect("FRUITYGER_QOBUZ_APP_ID is required to test this module"),
-            app_secret: std::env::var("FRUITYGER_QOBUZ_APP_SECRET")
-                .expect("FRUITYGER_QOBUZ_APP_SECRET is required to test this module"),
-        });
+        let client = Qobuz::new(Config::from_env().expect(
+            "FRUITYGER_QOBUZ_TOKEN, FRUITYGER_QOBUZ_APP_ID and FRUITYGER_QOBUZ_APP_SECRET are required to test this module",
+        ));
         let results = client.search(&query, 0).await.unwrap();
-        let stream = client.get_stream(&results.tracks[0].id).await.unwrap();
-        save_audio_stream(stream, Path::new("/tmp"), "qobuz_test")
+        let stream = client
+            .get_stream(&QobuzTrackId::try_from(results.tracks[0].id.as_str()).unwrap())
+            .await
+            .unwrap();
+        save_audio_stream(stream, Path::new("/tmp"), "qobuz_test", no_op_progress)
             .await
             .unwrap();
     }
@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use reqwest::{RequestBuilder, Response};
+use serde::Deserialize;
+
+use crate::error::Error;
+
+/// How often and how long to wait before repeating a request that failed
+/// with a timeout or a server error
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts including the first one, 1 disables retries
+    pub max_attempts: u32,
+    /// Delay before the second attempt, doubled for every attempt after it
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Waits a random 50-100% of the delay, so clients that failed
+    /// together do not retry together
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay_ms: 500,
+            max_delay_ms: 5000,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Sends every request once
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Delay after the failed `attempt`, counting from 1
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .initial_delay_ms
+            .saturating_mul(1 << (attempt - 1).min(16))
+            .min(self.max_delay_ms);
+        if !self.jitter {
+            return Duration::from_millis(delay);
+        }
+        let random = RandomState::new().build_hasher().finish();
+        Duration::from_millis(delay / 2 + random % (delay / 2 + 1))
+    }
+}
+
+fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::RequestError(e) => e.is_timeout() || e.status().is_some_and(|s| s.is_server_error()),
+        Error::ContextError { source, .. } => is_transient(source),
+        _ => false,
+    }
+}
+
+/// Runs `f` until it succeeds, fails with an error that is not a timeout or
+/// server error, or `policy.max_attempts` is reached
+pub async fn retry<F, Fut, T>(policy: &RetryPolicy, mut f: F) -> Result<T, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Err(error) if attempt < policy.max_attempts && is_transient(&error) => {
+                tokio::time::sleep(policy.delay(attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Sends `request` under `policy`. Server error responses are retried as
+/// well, the last one is returned as is so callers map its status as usual.
+pub(crate) async fn send_with_retry(
+    policy: &RetryPolicy,
+    request: RequestBuilder,
+) -> Result<Response, Error> {
+    let mut attempt = 1;
+    loop {
+        // Streaming bodies cannot be cloned, those requests are sent once
+        let Some(next) = request
+            .try_clone()
+            .filter(|_| attempt < policy.max_attempts)
+        else {
+            return Ok(request.send().await?);
+        };
+        match next.send().await {
+            Ok(response) if response.status().is_server_error() => {}
+            Err(error) if error.is_timeout() => {}
+            result => return Ok(result?),
+        }
+        tokio::time::sleep(policy.delay(attempt)).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::atomic::{AtomicU32, Ordering},
+        time::Duration,
+    };
+
+    use reqwest::StatusCode;
+
+    use crate::{
+        error::Error,
+        retry::{RetryPolicy, retry},
+    };
+
+    #[test]
+    fn delay() {
+        let policy = RetryPolicy {
+            jitter: false,
+            ..Default::default()
+        };
+        let delays = (1..=5).map(|a| policy.delay(a)).collect::<Vec<_>>();
+        assert_eq!(
+            delays,
+            [500, 1000, 2000, 4000, 5000].map(Duration::from_millis)
+        );
+
+        let policy = RetryPolicy::default();
+        for _ in 0..100 {
+            let delay = policy.delay(2);
+            assert!((Duration::from_millis(500)..=Duration::from_millis(1000)).contains(&delay));
+        }
+    }
+
+    #[tokio::test]
+    async fn retries() {
+        let policy = RetryPolicy {
+            initial_delay_ms: 1,
+            ..Default::default()
+        };
+        let server_error = || {
+            let response = http::Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .body("")
+                .unwrap();
+            Error::from(
+                reqwest::Response::from(response)
+                    .error_for_status()
+                    .unwrap_err(),
+            )
+        };
+
+        let attempts = AtomicU32::new(0);
+        let result = retry(&policy, || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(server_error()),
+                n => Ok(n),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 1);
+
+        let attempts = AtomicU32::new(0);
+        let result = retry(&policy, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(server_error())
+        })
+        .await;
+        assert!(matches!(result, Err(Error::RequestError(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        // Anything but timeouts and server errors fails right away
+        let attempts = AtomicU32::new(0);
+        let result = retry(&policy, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(Error::AuthenticationError("invalid token".to_owned()))
+        })
+        .await;
+        assert!(matches!(result, Err(Error::AuthenticationError(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
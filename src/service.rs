@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use async_trait::async_trait;
+
+use crate::{
+    AudioStream, SearchResults,
+    deezer::Deezer,
+    error::Error,
+    format::HifiQuality,
+    hifi::Hifi,
+    id::{HifiTrackId, QobuzTrackId, YandexTrackId},
+    qobuz::Qobuz,
+    yandex::Yandex,
+};
+
+/// Operations every backend supports, so backends (including third-party
+/// ones) can be stored as `Box<dyn Service>` and used interchangeably
+#[async_trait]
+pub trait Service: Send + Sync {
+    fn service_name(&self) -> &'static str;
+
+    async fn search(&self, query: &str, page: usize) -> Result<SearchResults, Error>;
+
+    /// Accepts either a link to the track or a bare track ID
+    async fn get_stream(&self, url: &str) -> Result<AudioStream, Error>;
+}
+
+#[async_trait]
+impl Service for Qobuz {
+    fn service_name(&self) -> &'static str {
+        Qobuz::service_name(self)
+    }
+
+    async fn search(&self, query: &str, page: usize) -> Result<SearchResults, Error> {
+        Qobuz::search(self, query, page).await
+    }
+
+    async fn get_stream(&self, url: &str) -> Result<AudioStream, Error> {
+        Qobuz::get_stream(self, &QobuzTrackId::try_from(url)?).await
+    }
+}
+
+#[async_trait]
+impl Service for Yandex {
+    fn service_name(&self) -> &'static str {
+        Yandex::service_name(self)
+    }
+
+    async fn search(&self, query: &str, page: usize) -> Result<SearchResults, Error> {
+        Yandex::search(self, query, page).await
+    }
+
+    async fn get_stream(&self, url: &str) -> Result<AudioStream, Error> {
+        Yandex::get_stream(self, &YandexTrackId::try_from(url)?).await
+    }
+}
+
+#[async_trait]
+impl Service for Deezer {
+    fn service_name(&self) -> &'static str {
+        Deezer::service_name(self)
+    }
+
+    async fn search(&self, query: &str, page: usize) -> Result<SearchResults, Error> {
+        Deezer::search(self, query, page).await
+    }
+
+    async fn get_stream(&self, url: &str) -> Result<AudioStream, Error> {
+        Deezer::get_stream(self, url).await
+    }
+}
+
+/// Streams are requested in the default quality, use [`Hifi::get_stream`]
+/// directly to pick another one
+#[async_trait]
+impl Service for Hifi {
+    fn service_name(&self) -> &'static str {
+        Hifi::service_name(self)
+    }
+
+    async fn search(&self, query: &str, page: usize) -> Result<SearchResults, Error> {
+        Hifi::search(self, query, page).await
+    }
+
+    async fn get_stream(&self, url: &str) -> Result<AudioStream, Error> {
+        Hifi::get_stream(self, &HifiTrackId::try_from(url)?, HifiQuality::default()).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use async_trait::async_trait;
+
+    use crate::{
+        AudioStream, SearchResults,
+        deezer::Deezer,
+        error::Error,
+        hifi::{self, Hifi},
+        qobuz::{self, Qobuz},
+        service::Service,
+        yandex::{self, Yandex},
+    };
+
+    struct Local;
+
+    #[async_trait]
+    impl Service for Local {
+        fn service_name(&self) -> &'static str {
+            "local"
+        }
+
+        async fn search(&self, _query: &str, page: usize) -> Result<SearchResults, Error> {
+            Ok(SearchResults::example(page + 1))
+        }
+
+        async fn get_stream(&self, _url: &str) -> Result<AudioStream, Error> {
+            Err(Error::UnsupportedFormatError)
+        }
+    }
+
+    #[tokio::test]
+    async fn dyn_service() {
+        let services: Vec<Box<dyn Service>> = vec![
+            Box::new(Qobuz::new(qobuz::Config::new("token", "app_id", "secret"))),
+            Box::new(Yandex::new(yandex::Config::new("token"))),
+            Box::new(Hifi::new(hifi::Config::new(vec![]))),
+            Box::new(Deezer::new("arl".to_owned())),
+            Box::new(Local),
+        ];
+        assert_eq!(
+            services
+                .iter()
+                .map(|s| s.service_name())
+                .collect::<Vec<_>>(),
+            ["qobuz", "yandex", "hifi", "deezer", "local"]
+        );
+        for service in &services[..4] {
+            assert!(matches!(
+                service.get_stream("not a track").await,
+                Err(Error::InvalidIdError(_))
+            ));
+        }
+        assert_eq!(
+            services[4].search("query", 1).await.unwrap().tracks.len(),
+            2
+        );
+    }
+}
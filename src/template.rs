@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use regex::Regex;
+
+use crate::{Metadata, error::Error, util::sanitize_path_component};
+
+const FIELDS: &[&str] = &[
+    "album",
+    "album_artist",
+    "artist",
+    "date",
+    "disc_number",
+    "genre",
+    "title",
+    "track_number",
+    "year",
+];
+
+/// File naming convention such as `{track_number} - {artist} - {title}`
+#[derive(Clone, Debug)]
+pub struct FileNameTemplate {
+    template: String,
+    regex: Regex,
+}
+
+impl FileNameTemplate {
+    pub fn new(template: &str) -> Result<Self, Error> {
+        let mut pattern = "^".to_owned();
+        let mut rest = template;
+        while let Some((literal, after)) = rest.split_once('{') {
+            let (field, after) = after
+                .split_once('}')
+                .ok_or_else(|| Error::TemplateError(format!("unclosed field in '{}'", template)))?;
+            if !FIELDS.contains(&field) {
+                return Err(Error::TemplateError(format!("unknown field '{}'", field)));
+            }
+            pattern += &regex::escape(literal);
+            pattern += &format!("(?P<{}>.+?)", field);
+            rest = after;
+        }
+        pattern += &regex::escape(rest);
+        pattern += "$";
+        Ok(Self {
+            template: template.to_owned(),
+            regex: Regex::new(&pattern).map_err(|e| Error::TemplateError(e.to_string()))?,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    /// Fills the template from `metadata`, missing fields are left empty.
+    /// Values are sanitized so they stay inside a single path component.
+    pub fn render(&self, metadata: &Metadata) -> String {
+        let mut name = String::new();
+        let mut rest = self.template.as_str();
+        while let Some((literal, after)) = rest.split_once('{') {
+            let (field, after) = after.split_once('}').unwrap_or((after, ""));
+            let value = match field {
+                "album" => metadata.album.clone(),
+                "album_artist" => metadata.album_artist.clone(),
+                "artist" => Some(metadata.artist.clone()),
+                "date" => metadata.date.clone(),
+                "disc_number" => metadata.disc.clone(),
+                "genre" => metadata.genre.clone(),
+                "title" => Some(metadata.title.clone()),
+                "track_number" => metadata.track.clone(),
+                "year" => metadata.year().map(|y| y.to_string()),
+                _ => None,
+            };
+            name += literal;
+            name += &sanitize_path_component(&value.unwrap_or_default());
+            rest = after;
+        }
+        name + rest
+    }
+
+    /// Field names and values of `name`, or `None` if it does not follow the template
+    pub fn captures<'a>(&self, name: &'a str) -> Option<Vec<(&'static str, &'a str)>> {
+        let captures = self.regex.captures(name)?;
+        Some(
+            FIELDS
+                .iter()
+                .filter_map(|field| Some((*field, captures.name(field)?.as_str())))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Metadata, template::FileNameTemplate};
+
+    #[test]
+    fn captures() {
+        let template = FileNameTemplate::new("{track_number} - {artist} - {title}").unwrap();
+        assert_eq!(
+            template.captures("01 - Artist - Title - Live").unwrap(),
+            [
+                ("artist", "Artist"),
+                ("title", "Title - Live"),
+                ("track_number", "01")
+            ]
+        );
+        assert!(template.captures("Artist - Title").is_none());
+
+        let template = FileNameTemplate::new("[{date}] {album}").unwrap();
+        assert!(template.captures("[2025] Album").is_some());
+        assert!(FileNameTemplate::new("{unknown}").is_err());
+        assert!(FileNameTemplate::new("{title").is_err());
+        assert!(FileNameTemplate::new("{title} {title}").is_err());
+    }
+
+    #[test]
+    fn render() {
+        let template = FileNameTemplate::new("{year} - {artist} - {title}").unwrap();
+        let metadata = Metadata {
+            artist: "AC/DC".to_owned(),
+            title: "Title".to_owned(),
+            date: Some("1980-07-25".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(template.render(&metadata), "1980 - AC_DC - Title");
+        assert_eq!(
+            template.render(&Metadata {
+                date: None,
+                ..metadata
+            }),
+            " - AC_DC - Title"
+        );
+    }
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{Album, Artist, SearchResults, Track};
+
+impl Artist {
+    pub fn example() -> Self {
+        Self {
+            id: "1000001".to_owned(),
+            name: "Test Artist".to_owned(),
+        }
+    }
+}
+
+impl Track {
+    pub fn example() -> Self {
+        Self {
+            id: "2000001".to_owned(),
+            url: "https://example.com/track/2000001".to_owned(),
+            title: "Test Track".to_owned(),
+            duration_ms: 215_000,
+            artists: vec![Artist::example()],
+            cover_url: "https://example.com/cover/3000001.jpg".to_owned(),
+            cover_uri_template: Some("https://example.com/cover/3000001/%%.jpg".to_owned()),
+            isrc: Some("GB-ABC-25-00001".to_owned()),
+            album_label_id: None,
+            album: Some("Test Album".to_owned()),
+            track_number: Some(1),
+        }
+    }
+
+    pub fn example_with_isrc(isrc: &str) -> Self {
+        Self {
+            isrc: Some(isrc.to_owned()),
+            ..Self::example()
+        }
+    }
+}
+
+impl Album {
+    pub fn example() -> Self {
+        Self {
+            id: "3000001".to_owned(),
+            url: "https://example.com/album/3000001".to_owned(),
+            title: "Test Album".to_owned(),
+            artists: vec![Artist::example()],
+            cover_url: "https://example.com/cover/3000001.jpg".to_owned(),
+            release_date: Some("2025-01-01".to_owned()),
+            track_count: 1,
+            disc_count: Some(1),
+            max_sampling_rate_khz: None,
+            max_bit_depth: None,
+            tracks: vec![Track::example()],
+        }
+    }
+}
+
+impl SearchResults {
+    pub fn example(n: usize) -> Self {
+        Self {
+            tracks: (1..=n)
+                .map(|i| Track {
+                    id: (2000000 + i).to_string(),
+                    url: format!("https://example.com/track/{}", 2000000 + i),
+                    title: format!("Test Track {}", i),
+                    isrc: Some(format!("GB-ABC-25-{:05}", i)),
+                    ..Track::example()
+                })
+                .collect(),
+            total: Some(n),
+            cache_hit: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{SearchResults, Track};
+
+    #[test]
+    fn fixtures() {
+        let track = Track::example_with_isrc("US-XYZ-25-00042");
+        assert_eq!(track.isrc.as_deref(), Some("US-XYZ-25-00042"));
+        assert_eq!(track.title, Track::example().title);
+
+        let results = SearchResults::example(3);
+        assert_eq!(results.tracks.len(), 3);
+        assert_eq!(results.tracks[2].isrc.as_deref(), Some("GB-ABC-25-00003"));
+    }
+}
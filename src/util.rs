@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use futures::{Stream, TryStreamExt, stream};
+use reqwest::{Response, StatusCode};
+use serde::Deserialize;
+use url::Url;
+
+use crate::{SearchResults, Track, error::Error};
+
+#[derive(Debug, Deserialize)]
+pub struct ApiEnvelope<T> {
+    pub result: T,
+}
+
+pub fn join_url(base: &str, path: &str) -> Result<Url, Error> {
+    Url::parse(base)
+        .map_err(|source| Error::UrlParseError {
+            url: base.to_owned(),
+            source,
+        })?
+        .join(path)
+        .map_err(|source| Error::UrlParseError {
+            url: path.to_owned(),
+            source,
+        })
+}
+
+/// Yandex hands out cover URIs without a scheme and with `%%` where the
+/// size goes, this keeps the placeholder and adds the scheme
+pub fn normalize_cover_url_template(uri: &str) -> String {
+    if uri.contains("://") {
+        uri.to_owned()
+    } else {
+        format!("https://{}", uri.trim_start_matches('/'))
+    }
+}
+
+pub(crate) fn normalize_isrc(isrc: &str) -> String {
+    isrc.replace('-', "").to_ascii_uppercase()
+}
+
+/// Checks the `CC-XXX-YY-NNNNN` shape, hyphens are optional
+pub fn is_valid_isrc(isrc: &str) -> bool {
+    let isrc = normalize_isrc(isrc);
+    let bytes = isrc.as_bytes();
+    bytes.len() == 12
+        && bytes[..2].iter().all(u8::is_ascii_alphabetic)
+        && bytes[2..5].iter().all(u8::is_ascii_alphanumeric)
+        && bytes[5..].iter().all(u8::is_ascii_digit)
+}
+
+pub fn isrc_eq(a: &str, b: &str) -> bool {
+    normalize_isrc(a) == normalize_isrc(b)
+}
+
+// Windows forbids all of these, other platforms only `/` and NUL
+const ILLEGAL_FILENAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|', '\0'];
+
+fn trim_filename(name: &str) -> String {
+    name.trim_matches(['.', ' ']).to_owned()
+}
+
+/// Replaces characters that are illegal in file names on any platform and
+/// trims the leading and trailing dots and spaces Windows rejects
+pub fn sanitize_filename(input: &str, replacement: char) -> String {
+    trim_filename(&input.replace(ILLEGAL_FILENAME_CHARS, &replacement.to_string()))
+}
+
+pub fn sanitize_path_component(input: &str) -> String {
+    sanitize_filename(input, '_')
+}
+
+/// Same as [`sanitize_filename`], but drops illegal characters entirely
+pub fn sanitize_filename_lossy(input: &str) -> String {
+    trim_filename(&input.replace(ILLEGAL_FILENAME_CHARS, ""))
+}
+
+pub fn check_status(response: Response, service_name: &str) -> Result<Response, Error> {
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return Ok(response);
+    }
+    Err(status_error(
+        status,
+        format!("{} returned {}", service_name, status),
+    ))
+}
+
+/// Picks the error variant matching an HTTP error status
+pub fn status_error(status: StatusCode, message: String) -> Error {
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Error::AuthenticationError(message),
+        StatusCode::PAYMENT_REQUIRED => Error::PaymentRequiredError(message),
+        StatusCode::TOO_MANY_REQUESTS => Error::RateLimitError(message),
+        _ => Error::ServiceError(message),
+    }
+}
+
+/// Requests pages from `search` starting at 0 until one comes back empty
+/// or the reported total is reached, and flattens them into tracks
+pub(crate) fn paginate<'a, F, Fut>(search: F) -> impl Stream<Item = Result<Track, Error>> + 'a
+where
+    F: Fn(usize) -> Fut + 'a,
+    Fut: Future<Output = Result<SearchResults, Error>> + 'a,
+{
+    stream::try_unfold(
+        (search, 0, 0, false),
+        |(search, page, fetched, done)| async move {
+            if done {
+                return Ok::<_, Error>(None);
+            }
+            let results = search(page).await?;
+            let fetched = fetched + results.tracks.len();
+            let done = results.tracks.is_empty() || results.total.is_some_and(|t| fetched >= t);
+            Ok(Some((results.tracks, (search, page + 1, fetched, done))))
+        },
+    )
+    .map_ok(|tracks| stream::iter(tracks.into_iter().map(Ok)))
+    .try_flatten()
+}
+
+#[cfg(test)]
+mod test {
+    use futures::TryStreamExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{
+        SearchResults, Track,
+        util::{
+            is_valid_isrc, isrc_eq, normalize_cover_url_template, paginate, sanitize_filename,
+            sanitize_filename_lossy, sanitize_path_component,
+        },
+    };
+
+    #[test]
+    fn cover_url_template() {
+        assert_eq!(
+            normalize_cover_url_template("avatars.yandex.net/get-music-content/1/%%"),
+            "https://avatars.yandex.net/get-music-content/1/%%"
+        );
+        assert_eq!(
+            normalize_cover_url_template("https://example.com/%%"),
+            "https://example.com/%%"
+        );
+        assert_eq!(
+            Track::example().cover_url_at_size(400, 400).as_deref(),
+            Some("https://example.com/cover/3000001/400x400.jpg")
+        );
+    }
+
+    #[tokio::test]
+    async fn pagination() {
+        let requests = AtomicUsize::new(0);
+        let search = |total: Option<usize>| {
+            let requests = &requests;
+            move |page: usize| {
+                requests.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    let tracks = (page * 2..(page * 2 + 2).min(5))
+                        .map(|i| Track {
+                            id: i.to_string(),
+                            ..Track::example()
+                        })
+                        .collect();
+                    Ok(SearchResults {
+                        tracks,
+                        total,
+                        cache_hit: false,
+                    })
+                }
+            }
+        };
+
+        let tracks = paginate(search(Some(5)))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let ids = tracks.iter().map(|t| t.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["0", "1", "2", "3", "4"]);
+        assert_eq!(requests.swap(0, Ordering::SeqCst), 3);
+
+        // Without a total the empty page ends the stream
+        let tracks = paginate(search(None))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(tracks.len(), 5);
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn isrc() {
+        assert!(is_valid_isrc("GB-ABC-25-00001"));
+        assert!(is_valid_isrc("usrc17607839"));
+        assert!(!is_valid_isrc("GB-ABC-25-0001"));
+        assert!(!is_valid_isrc("12-ABC-25-00001"));
+        assert!(isrc_eq("GB-ABC-25-00001", "gbabc2500001"));
+    }
+
+    #[test]
+    fn sanitize() {
+        assert_eq!(sanitize_filename("AC/DC", '_'), "AC_DC");
+        assert_eq!(sanitize_filename("a\\b:c*d?e", ' '), "a b c d e");
+        assert_eq!(
+            sanitize_path_component("\"quoted\" <x>|y\0"),
+            "_quoted_ _x__y_"
+        );
+        assert_eq!(sanitize_path_component(" ..hidden. "), "hidden");
+        assert_eq!(
+            sanitize_path_component("Sigur Rós: Ágætis byrjun"),
+            "Sigur Rós_ Ágætis byrjun"
+        );
+        assert_eq!(sanitize_filename_lossy("What? / Why*"), "What  Why");
+        assert_eq!(sanitize_filename_lossy("東京/事変"), "東京事変");
+    }
+}
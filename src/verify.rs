@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::path::Path;
+
+use ffmpeg_next::{codec, decoder, error::EAGAIN, frame, media};
+use md5::{Digest, Md5};
+use tokio::{fs::File, io::AsyncReadExt};
+
+use crate::error::Error;
+
+pub struct IntegrityCheck;
+
+impl IntegrityCheck {
+    pub async fn md5(path: &Path) -> Result<String, Error> {
+        let mut file = File::open(path).await?;
+        let mut hasher = Md5::new();
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let n = file.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    pub async fn verify_md5(path: &Path, expected: &str) -> Result<bool, Error> {
+        Ok(Self::md5(path).await?.eq_ignore_ascii_case(expected))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct VerifyOptions {
+    /// Decode every packet instead of only demuxing, slow but catches
+    /// corrupt audio inside a valid container
+    pub deep_check: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntegrityReport {
+    pub total_packets: u64,
+    pub error_packets: u64,
+    pub duration_ms: u64,
+    pub is_likely_corrupt: bool,
+}
+
+pub fn verify_audio_integrity(path: &Path) -> Result<IntegrityReport, Error> {
+    verify_audio_integrity_with_options(path, &VerifyOptions::default())
+}
+
+pub fn verify_audio_integrity_with_options(
+    path: &Path,
+    options: &VerifyOptions,
+) -> Result<IntegrityReport, Error> {
+    let mut input = ffmpeg_next::format::input(&path)?;
+    let (index, parameters) = input
+        .streams()
+        .best(media::Type::Audio)
+        .map(|s| (s.index(), s.parameters()))
+        .ok_or(ffmpeg_next::Error::StreamNotFound)?;
+    let mut decoder = match options.deep_check {
+        true => Some(
+            codec::Context::from_parameters(parameters)?
+                .decoder()
+                .audio()?,
+        ),
+        false => None,
+    };
+
+    let mut report = IntegrityReport {
+        total_packets: 0,
+        error_packets: 0,
+        // Container duration is in AV_TIME_BASE units (microseconds)
+        duration_ms: u64::try_from(input.duration()).unwrap_or(0) / 1000,
+        is_likely_corrupt: false,
+    };
+    let mut frame = frame::Audio::empty();
+    for (stream, packet) in input.packets() {
+        if stream.index() != index {
+            continue;
+        }
+        report.total_packets += 1;
+        let Some(decoder) = &mut decoder else {
+            continue;
+        };
+        match decoder.send_packet(&packet) {
+            Ok(()) => report.error_packets += drain(decoder, &mut frame)?,
+            Err(ffmpeg_next::Error::InvalidData) => report.error_packets += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    if let Some(decoder) = &mut decoder {
+        decoder.send_eof()?;
+        report.error_packets += drain(decoder, &mut frame)?;
+    }
+    report.is_likely_corrupt = report.error_packets > 0;
+    Ok(report)
+}
+
+// Some decoders only report corrupt data once the frame is received
+fn drain(decoder: &mut decoder::Audio, frame: &mut frame::Audio) -> Result<u64, Error> {
+    let mut errors = 0;
+    loop {
+        match decoder.receive_frame(frame) {
+            Ok(()) => {}
+            Err(ffmpeg_next::Error::InvalidData) => errors += 1,
+            Err(ffmpeg_next::Error::Eof) => return Ok(errors),
+            Err(ffmpeg_next::Error::Other { errno: EAGAIN }) => return Ok(errors),
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use crate::verify::{IntegrityCheck, VerifyOptions, verify_audio_integrity_with_options};
+
+    #[test]
+    fn verify_audio_integrity_test() {
+        let path = std::env::var("AUDIO_PATH").unwrap_or("/tmp/audio.flac".to_owned());
+        let report = verify_audio_integrity_with_options(
+            Path::new(&path),
+            &VerifyOptions { deep_check: true },
+        )
+        .unwrap();
+        assert!(report.total_packets > 0);
+        assert!(!report.is_likely_corrupt);
+    }
+
+    #[tokio::test]
+    async fn verify_md5() {
+        let path = std::env::temp_dir().join("fruityger_verify_md5_test");
+        tokio::fs::write(&path, b"hello world").await.unwrap();
+        assert!(
+            IntegrityCheck::verify_md5(&path, "5EB63BBBE01EEED093CB22BB8F5ACDC3")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !IntegrityCheck::verify_md5(&path, "d41d8cd98f00b204e9800998ecf8427e")
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -1,29 +1,228 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
-use crate::{AudioFormat, AudioStream, Error, SearchResults, const_headers};
+use crate::{
+    Album, ArtistDetail, AudioFormat, AudioStream, Error, Metadata, SearchResults, Track,
+    const_headers,
+    format::YandexQuality,
+    id::{StationId, YandexTrackId},
+    lyrics::LyricsResult,
+    retry::{RetryPolicy, send_with_retry},
+    util::{ApiEnvelope, check_status, join_url, normalize_cover_url_template, paginate},
+};
 use base64::{Engine, prelude::BASE64_STANDARD_NO_PAD};
 use chrono::Utc;
+use futures::Stream;
 use hmac::{Hmac, Mac};
-use reqwest::{Client, Method, RequestBuilder, redirect::Policy};
-use serde::Deserialize;
-use serde_json::Value;
+use reqwest::{Client, Method, RequestBuilder, Response, redirect::Policy};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use sha2::Sha256;
-use url::Url;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
 
 type HmacSha256 = Hmac<Sha256>;
 
+const DEFAULT_BASE_URL: &str = "https://api.music.yandex.net";
 const SIGN_KEY: &[u8] = b"kzqU4XhfCaY6B6JTHODeq5";
+const DOWNLOAD_SALT: &str = "XGRlBW9FXlekgbPrRHuSiA";
+const TRACKS_PAGE_SIZE: usize = 100;
+
+fn sign(message: &str) -> String {
+    let mut h = HmacSha256::new_from_slice(SIGN_KEY).unwrap();
+    h.update(message.as_bytes());
+    BASE64_STANDARD_NO_PAD.encode(h.finalize().into_bytes())
+}
+
+/// Passing this to [`resolve_cover_uri`] requests the cover in its original size
+pub const ORIGINAL_COVER_SIZE: u32 = 0;
+
+/// Turns a Yandex cover URI into a URL for a `size`x`size` image
+pub fn resolve_cover_uri(uri: &str, size: u32) -> String {
+    let size = match size {
+        ORIGINAL_COVER_SIZE => "orig".to_owned(),
+        size => format!("{}x{}", size, size),
+    };
+    normalize_cover_url_template(uri).replace("%%", &size)
+}
+
+#[derive(Clone, Copy)]
+enum SearchType {
+    Track,
+    Album,
+    Artist,
+    All,
+}
+
+impl SearchType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Track => "track",
+            Self::Album => "album",
+            Self::Artist => "artist",
+            Self::All => "all",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ChartEntry {
+    pub position: u32,
+    pub track: Track,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Podcast {
+    pub id: String,
+    pub title: String,
+    pub author: String,
+    pub description: Option<String>,
+    pub cover_url: String,
+    pub episodes: Vec<PodcastEpisode>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PodcastEpisode {
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub duration_ms: usize,
+    pub published_at: Option<String>,
+    /// Only resolved by [`Yandex::get_podcast_episode`], album listings carry no stream URLs
+    pub audio_url: Option<String>,
+}
+
+impl PodcastEpisode {
+    pub fn metadata(&self, podcast: &Podcast) -> Metadata {
+        Metadata {
+            title: self.title.clone(),
+            artist: podcast.author.clone(),
+            album: Some(podcast.title.clone()),
+            comment: self.description.clone(),
+            date: self.published_at.clone(),
+            genre: Some("Podcast".to_owned()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Lyrics {
+    pub text: String,
+    pub has_sync: bool,
+    /// LRC text, only present if `has_sync` is set
+    pub sync_data: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Station {
+    pub id: StationId,
+    pub name: String,
+    pub description: String,
+    pub cover_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct StationResponse {
+    pub tracks: Vec<Track>,
+    /// Identifies the batch the tracks belong to in playback feedback
+    pub radio_session_id: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PlaylistInfo {
+    /// `{owner uid}:{kind}`, playlist kinds are only unique per user
+    pub id: String,
+    pub url: String,
+    pub name: String,
+    pub track_count: usize,
+    pub cover_url: Option<String>,
+}
+
+/// Every entity type matching a query, see [`Yandex::search_unified`]
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct UnifiedSearchResults {
+    pub tracks: Vec<Track>,
+    pub albums: Vec<Album>,
+    pub artists: Vec<ArtistDetail>,
+    pub playlists: Vec<PlaylistInfo>,
+}
+
+impl UnifiedSearchResults {
+    pub fn into_search_results(self) -> SearchResults {
+        SearchResults {
+            tracks: self.tracks,
+            ..Default::default()
+        }
+    }
+}
+
+/// A file the track can be downloaded as, see [`Yandex::get_download_options`]
+#[derive(Clone, Debug, Serialize)]
+pub struct DownloadOption {
+    pub format: AudioFormat,
+    pub url: String,
+    pub filesize_bytes: Option<u64>,
+}
+
+/// Everything the user liked
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct UserLibrary {
+    pub tracks: Vec<Track>,
+    pub albums: Vec<Album>,
+    pub artists: Vec<ArtistDetail>,
+}
+
+impl UserLibrary {
+    pub fn track_count(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn album_count(&self) -> usize {
+        self.albums.len()
+    }
+}
 
 #[derive(Clone)]
 pub struct Yandex {
     client: reqwest::Client,
     config: Config,
+    base_url: String,
+    // Looked up on first use unless the config provides it
+    user_id: Arc<OnceCell<u64>>,
 }
 
 #[derive(Clone, Deserialize)]
 pub struct Config {
     token: String,
+    #[serde(default)]
+    user_id: Option<u64>,
+    #[serde(default)]
+    retry: RetryPolicy,
+}
+
+impl Config {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+            user_id: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Retries timeouts and server errors according to `retry`
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Reads the token from `FRUITYGER_YANDEX_TOKEN`
+    pub fn from_env() -> Result<Self, Error> {
+        Ok(Self {
+            token: std::env::var("FRUITYGER_YANDEX_TOKEN")?,
+            user_id: None,
+            retry: RetryPolicy::default(),
+        })
+    }
 }
 
 impl Yandex {
@@ -37,103 +236,486 @@ impl Yandex {
                 .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) YandexMusic/5.18.2 Chrome/122.0.6261.156 Electron/29.4.6 Safari/537.36")
                 .build()
                 .unwrap(),
+            user_id: Arc::new(OnceCell::new_with(config.user_id)),
             config,
+            base_url: DEFAULT_BASE_URL.to_owned(),
         }
     }
 
-    fn builder<S: AsRef<str>>(&self, method: Method, url: S) -> RequestBuilder {
-        self.client
-            .request(
-                method,
-                Url::parse("https://api.music.yandex.net")
-                    .unwrap()
-                    .join(url.as_ref())
-                    .unwrap(),
-            )
-            .header("authorization", format!("OAuth {}", self.config.token))
+    /// Sends API requests to `base_url` instead of the Yandex API, e.g. a mock server
+    pub fn with_base_url(config: Config, base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            ..Self::new(config)
+        }
+    }
+
+    pub fn service_name(&self) -> &'static str {
+        "yandex"
+    }
+
+    pub fn supported_formats(&self) -> Vec<AudioFormat> {
+        vec![
+            AudioFormat::FlacMp4,
+            AudioFormat::Aac(0),
+            AudioFormat::Mp3(0),
+        ]
+    }
+
+    fn builder<S: AsRef<str>>(&self, method: Method, url: S) -> Result<RequestBuilder, Error> {
+        Ok(self
+            .client
+            .request(method, join_url(&self.base_url, url.as_ref())?)
+            .header("authorization", format!("OAuth {}", self.config.token)))
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, Error> {
+        Ok(check_status(
+            send_with_retry(&self.config.retry, request).await?,
+            self.service_name(),
+        )?
+        .json::<ApiEnvelope<T>>()
+        .await?
+        .result)
+    }
+
+    async fn search_by_type<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        page: usize,
+        search_type: SearchType,
+    ) -> Result<T, Error> {
+        self.send(self.builder(Method::GET, "/search")?.query(&[
+            ("text", query),
+            ("type", search_type.as_str()),
+            ("page", &page.to_string()),
+        ]))
+        .await
     }
 
     pub async fn search(&self, query: &str, page: usize) -> Result<SearchResults, Error> {
-        let text = self
-            .builder(Method::GET, "/search")
-            .query(&[
-                ("text", query),
-                ("type", "track"),
-                ("page", &page.to_string()),
-            ])
-            .send()
-            .await?
-            .text()
+        Ok(self
+            .search_by_type::<data::SearchResponse>(query, page, SearchType::Track)
+            .await?
+            .into())
+    }
+
+    /// Fetches search pages on demand and yields their tracks one by one
+    pub fn search_all<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> impl Stream<Item = Result<Track, Error>> + 'a {
+        paginate(move |page| self.search(query, page))
+    }
+
+    pub async fn search_albums(&self, query: &str, page: usize) -> Result<Vec<Album>, Error> {
+        Ok(self
+            .search_by_type::<data::SearchAlbumResponse>(query, page, SearchType::Album)
+            .await?
+            .albums
+            .results
+            .into_iter()
+            .map(data::Album::into)
+            .collect())
+    }
+
+    pub async fn get_album(&self, id: &str) -> Result<Album, Error> {
+        Ok(self
+            .send::<data::AlbumWithTracks>(
+                self.builder(Method::GET, format!("/albums/{}/with-tracks", id))?,
+            )
+            .await?
+            .into())
+    }
+
+    pub async fn search_artists(
+        &self,
+        query: &str,
+        page: usize,
+    ) -> Result<Vec<ArtistDetail>, Error> {
+        Ok(self
+            .search_by_type::<data::SearchArtistResponse>(query, page, SearchType::Artist)
+            .await?
+            .artists
+            .results
+            .into_iter()
+            .map(data::Artist::into)
+            .collect())
+    }
+
+    /// Searches tracks, albums, artists and playlists with a single request
+    pub async fn search_unified(
+        &self,
+        query: &str,
+        page: usize,
+    ) -> Result<UnifiedSearchResults, Error> {
+        Ok(self
+            .search_by_type::<data::SearchAllResponse>(query, page, SearchType::All)
+            .await?
+            .into())
+    }
+
+    pub async fn get_chart(&self, country: Option<&str>) -> Result<Vec<ChartEntry>, Error> {
+        let url = match country {
+            Some(country) => format!("/landing3/chart/{}", country),
+            None => "/landing3/chart".to_owned(),
+        };
+        Ok(self
+            .send::<data::ChartResponse>(self.builder(Method::GET, url)?)
+            .await?
+            .chart
+            .tracks
+            .into_iter()
+            .map(data::ChartTrack::into)
+            .collect())
+    }
+
+    pub async fn get_new_releases(&self) -> Result<Vec<Album>, Error> {
+        let ids = self
+            .send::<data::NewReleasesResponse>(self.builder(Method::GET, "/landing3/new-releases")?)
+            .await?
+            .new_releases
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        Ok(self
+            .send::<Vec<data::Album>>(
+                self.builder(Method::POST, "/albums")?
+                    .form(&[("album-ids", ids)]),
+            )
+            .await?
+            .into_iter()
+            .map(data::Album::into)
+            .collect())
+    }
+
+    pub async fn get_similar_tracks(&self, track_id: &str) -> Result<Vec<Track>, Error> {
+        Ok(self
+            .send::<data::SimilarTracksResponse>(
+                self.builder(Method::GET, format!("/tracks/{}/similar", track_id))?,
+            )
+            .await?
+            .similar_tracks
+            .into_iter()
+            .map(data::Track::into)
+            .collect())
+    }
+
+    pub async fn get_track_lyrics(&self, track_id: &str) -> Result<Option<LyricsResult>, Error> {
+        Ok(self
+            .send::<data::SupplementResponse>(
+                self.builder(Method::GET, format!("/tracks/{}/supplement", track_id))?,
+            )
+            .await?
+            .lyrics
+            .map(data::LyricsData::into))
+    }
+
+    async fn get_lyrics_text(&self, id: &str, format: &str) -> Result<String, Error> {
+        let ts = Utc::now().timestamp().to_string();
+        let sign = sign(&format!("{}{}", id, ts));
+        let lyrics = self
+            .send::<data::LyricsInfo>(
+                self.builder(Method::GET, format!("/tracks/{}/lyrics", id))?
+                    .query(&[("format", format), ("timeStamp", &ts), ("sign", &sign)]),
+            )
+            .await?;
+        Ok(check_status(
+            send_with_retry(&self.config.retry, self.client.get(lyrics.download_url)).await?,
+            self.service_name(),
+        )?
+        .text()
+        .await?)
+    }
+
+    /// Tracks without synchronized lyrics still return their plain text
+    pub async fn get_lyrics(&self, id: &str) -> Result<Lyrics, Error> {
+        let text = self.get_lyrics_text(id, "TEXT").await?;
+        let sync_data = match self.get_lyrics_text(id, "LRC").await {
+            Ok(lrc) => Some(lrc),
+            Err(Error::ServiceError(_)) => None,
+            Err(e) => return Err(e),
+        };
+        Ok(Lyrics {
+            text,
+            has_sync: sync_data.is_some(),
+            sync_data,
+        })
+    }
+
+    pub async fn get_podcast(&self, id: &str) -> Result<Podcast, Error> {
+        let album = self
+            .send::<data::PodcastAlbum>(
+                self.builder(Method::GET, format!("/albums/{}/with-tracks", id))?,
+            )
+            .await?;
+        if album.album_type.as_deref() != Some("podcast") {
+            return Err(Error::ServiceError(format!(
+                "album {} is not a podcast",
+                id
+            )));
+        }
+        Ok(album.into())
+    }
+
+    pub async fn get_podcast_episode(&self, id: &YandexTrackId) -> Result<PodcastEpisode, Error> {
+        let mut episode: PodcastEpisode = self
+            .send::<Vec<data::PodcastEpisode>>(
+                self.builder(Method::GET, format!("/tracks/{}", id))?,
+            )
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::ServiceError(format!("episode {} not found", id)))?
+            .into();
+
+        // Podcasts are free to stream, so the legacy download-info flow works
+        // without the signed get-file-info request
+        let info = self
+            .send::<Vec<data::TrackDownloadInfo>>(
+                self.builder(Method::GET, format!("/tracks/{}/download-info", id))?,
+            )
+            .await?
+            .into_iter()
+            .filter(|i| i.codec == "mp3")
+            .max_by_key(|i| i.bitrate_in_kbps)
+            .ok_or(Error::UnsupportedFormatError)?;
+        let location = check_status(
+            send_with_retry(
+                &self.config.retry,
+                self.client
+                    .get(info.download_info_url)
+                    .query(&[("format", "json")]),
+            )
+            .await?,
+            self.service_name(),
+        )?
+        .json::<data::DownloadLocation>()
+        .await?;
+        episode.audio_url = Some(location.into());
+        Ok(episode)
+    }
+
+    async fn user_id(&self) -> Result<u64, Error> {
+        self.user_id
+            .get_or_try_init(|| async {
+                self.send::<data::AccountStatus>(self.builder(Method::GET, "/account/status")?)
+                    .await?
+                    .account
+                    .uid
+                    .ok_or_else(|| Error::AuthenticationError("account has no user id".to_owned()))
+            })
+            .await
+            .copied()
+    }
+
+    /// Liked tracks, albums and artists. Likes only list track ids, the
+    /// tracks themselves are fetched in pages of 100.
+    pub async fn get_user_library(&self) -> Result<UserLibrary, Error> {
+        let uid = self.user_id().await?;
+        let likes = self
+            .send::<data::LikedTracksResponse>(
+                self.builder(Method::GET, format!("/users/{}/likes/tracks", uid))?,
+            )
             .await?;
-        let value = serde_json::from_str::<Value>(&text)?;
-        let data = serde_json::from_value::<data::ApiResponse<data::SearchResponse>>(value)?;
-        Ok(data.into())
+        let mut tracks = vec![];
+        for page in likes.library.tracks.chunks(TRACKS_PAGE_SIZE) {
+            let ids = page
+                .iter()
+                .map(|t| t.id.as_str())
+                .collect::<Vec<_>>()
+                .join(",");
+            tracks.extend(
+                self.send::<Vec<data::Track>>(
+                    self.builder(Method::POST, "/tracks")?
+                        .form(&[("track-ids", ids)]),
+                )
+                .await?
+                .into_iter()
+                .map(data::Track::into),
+            );
+        }
+
+        let albums = self
+            .send::<Vec<data::LikedAlbum>>(
+                self.builder(Method::GET, format!("/users/{}/likes/albums", uid))?
+                    .query(&[("rich", "true")]),
+            )
+            .await?
+            .into_iter()
+            .map(|a| a.album.into())
+            .collect();
+        let artists = self
+            .send::<Vec<data::Artist>>(
+                self.builder(Method::GET, format!("/users/{}/likes/artists", uid))?
+                    .query(&[("with-timestamps", "false")]),
+            )
+            .await?
+            .into_iter()
+            .map(data::Artist::into)
+            .collect();
+
+        Ok(UserLibrary {
+            tracks,
+            albums,
+            artists,
+        })
+    }
+
+    pub async fn get_stations(&self) -> Result<Vec<Station>, Error> {
+        Ok(self
+            .send::<Vec<data::StationItem>>(self.builder(Method::GET, "/rotor/stations/list")?)
+            .await?
+            .into_iter()
+            .map(data::StationItem::into)
+            .collect())
+    }
+
+    /// Passing the last played track continues the station queue after it
+    pub async fn get_station_tracks(
+        &self,
+        station_id: &StationId,
+        last_track_id: Option<&str>,
+    ) -> Result<StationResponse, Error> {
+        let mut request = self
+            .builder(Method::GET, format!("/rotor/station/{}/tracks", station_id))?
+            .query(&[("settings2", "true")]);
+        if let Some(last_track_id) = last_track_id {
+            request = request.query(&[("queue", last_track_id)]);
+        }
+        Ok(self
+            .send::<data::StationTracksResponse>(request)
+            .await?
+            .into())
     }
 
-    pub async fn get_stream(&self, id: &str) -> Result<AudioStream, Error> {
+    async fn get_file_info(
+        &self,
+        id: &YandexTrackId,
+        quality: YandexQuality,
+    ) -> Result<data::DownloadInfo, Error> {
         let ts = Utc::now().timestamp();
         let mut query = [
             ("ts", ts.to_string()),
             ("trackId", id.to_string()),
-            ("quality", "lossless".to_string()),
+            ("quality", quality.as_str().to_string()),
             ("codecs", "flac,flac-mp4,aac,aac-mp4,mp3".to_string()),
             ("transports", "raw".to_string()),
             ("sign", String::new()),
         ];
-        query[5].1 = {
-            let mut h = HmacSha256::new_from_slice(SIGN_KEY).unwrap();
-            h.update(
-                format!(
-                    "{}{}{}{}{}",
-                    query[0].1,
-                    query[1].1,
-                    query[2].1,
-                    query[3].1.replace(',', ""),
-                    query[4].1.replace(',', "")
-                )
-                .as_bytes(),
-            );
-            BASE64_STANDARD_NO_PAD.encode(h.finalize().into_bytes())
-        };
+        query[5].1 = sign(&format!(
+            "{}{}{}{}{}",
+            query[0].1,
+            query[1].1,
+            query[2].1,
+            query[3].1.replace(',', ""),
+            query[4].1.replace(',', "")
+        ));
 
-        let response = self
-            .builder(Method::GET, "/get-file-info")
-            .query(&query)
-            .send()
+        Ok(self
+            .send::<data::GetFileInfoResponse>(
+                self.builder(Method::GET, "/get-file-info")?.query(&query),
+            )
             .await?
-            .json::<data::ApiResponse<data::GetFileInfoResponse>>()
-            .await?;
+            .download_info)
+    }
 
-        let format = match response.result.download_info.codec.as_str() {
-            "mp3" => AudioFormat::Mp3(response.result.download_info.bitrate),
-            "aac-mp4" => AudioFormat::Aac(response.result.download_info.bitrate),
-            "flac-mp4" => AudioFormat::Flac,
-            _ => return Err(Error::UnsupportedFormatError),
-        };
+    /// Asks for every quality without downloading anything. The service falls
+    /// back to the best quality the account can access, so options that
+    /// resolve to the same format are only listed once.
+    pub async fn get_download_options(
+        &self,
+        id: &YandexTrackId,
+    ) -> Result<Vec<DownloadOption>, Error> {
+        let mut options: Vec<DownloadOption> = vec![];
+        for quality in [
+            YandexQuality::Lossless,
+            YandexQuality::High,
+            YandexQuality::Low,
+        ] {
+            let info = self.get_file_info(id, quality).await?;
+            let Ok(format) = info.format() else {
+                continue;
+            };
+            if options.iter().all(|o| o.format != format) {
+                options.push(DownloadOption {
+                    format,
+                    url: info.url,
+                    filesize_bytes: info.size,
+                });
+            }
+        }
+        Ok(options)
+    }
+
+    /// Starts downloading a URL from [`Yandex::get_download_options`]
+    pub async fn stream_from_url(&self, url: &str) -> Result<Response, Error> {
+        check_status(
+            send_with_retry(&self.config.retry, self.client.get(url)).await?,
+            self.service_name(),
+        )
+    }
 
+    pub async fn get_stream(&self, id: &YandexTrackId) -> Result<AudioStream, Error> {
+        let info = self.get_file_info(id, YandexQuality::Lossless).await?;
+        let format = info.format()?;
         Ok(AudioStream {
-            response: self
-                .client
-                .get(response.result.download_info.url)
-                .send()
-                .await?,
+            response: self.stream_from_url(&info.url).await?,
             format,
+            md5: None,
         })
     }
 }
 
 mod data {
-    use crate::SearchResults;
+    use crate::{
+        AudioFormat, Error, SearchResults,
+        util::normalize_cover_url_template,
+        yandex::{DOWNLOAD_SALT, ORIGINAL_COVER_SIZE, resolve_cover_uri},
+    };
+    use md5::{Digest, Md5};
     use serde::Deserialize;
 
     #[derive(Debug, Deserialize)]
-    pub struct ApiResponse<T> {
-        pub result: T,
+    pub struct SearchResponse {
+        pub tracks: Results<Track>,
     }
 
     #[derive(Debug, Deserialize)]
-    pub struct SearchResponse {
-        pub tracks: TracksData,
+    pub struct SearchAlbumResponse {
+        pub albums: Results<Album>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct SearchArtistResponse {
+        pub artists: Results<Artist>,
+    }
+
+    /// Entity types without matches are left out of the response
+    #[derive(Debug, Deserialize)]
+    pub struct SearchAllResponse {
+        pub tracks: Option<Results<Track>>,
+        pub albums: Option<Results<Album>>,
+        pub artists: Option<Results<Artist>>,
+        pub playlists: Option<Results<Playlist>>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Playlist {
+        pub uid: u64,
+        pub kind: u64,
+        pub title: String,
+        #[serde(default)]
+        pub track_count: usize,
+        pub owner: Option<PlaylistOwner>,
+        pub cover: Option<Cover>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct PlaylistOwner {
+        pub login: String,
     }
 
     #[derive(Debug, Deserialize)]
@@ -146,13 +728,191 @@ mod data {
     #[serde(rename_all = "camelCase")]
     pub struct DownloadInfo {
         pub codec: String,
-        pub bitrate: u16,
+        pub bitrate: Option<u16>,
         pub url: String,
+        pub size: Option<u64>,
+    }
+
+    impl DownloadInfo {
+        pub fn format(&self) -> Result<AudioFormat, Error> {
+            let bitrate = self.bitrate.unwrap_or(0);
+            match self.codec.as_str() {
+                "mp3" => Ok(AudioFormat::Mp3(bitrate)),
+                "aac-mp4" => Ok(AudioFormat::Aac(bitrate)),
+                "flac-mp4" => Ok(AudioFormat::FlacMp4),
+                _ => Err(Error::UnsupportedFormatError),
+            }
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct ChartResponse {
+        pub chart: ChartPlaylist,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct ChartPlaylist {
+        pub tracks: Vec<ChartTrack>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct ChartTrack {
+        pub track: Track,
+        pub chart: ChartPosition,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct ChartPosition {
+        pub position: u32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SimilarTracksResponse {
+        pub similar_tracks: Vec<Track>,
     }
 
     #[derive(Debug, Deserialize)]
-    pub struct TracksData {
-        pub results: Vec<Track>,
+    pub struct SupplementResponse {
+        pub lyrics: Option<LyricsData>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LyricsData {
+        pub full_lyrics: String,
+        pub sync_lyrics: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LyricsInfo {
+        pub download_url: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct NewReleasesResponse {
+        pub new_releases: Vec<u64>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PodcastAlbum {
+        pub id: u64,
+        pub title: String,
+        #[serde(rename = "type")]
+        pub album_type: Option<String>,
+        #[serde(default)]
+        pub artists: Vec<Artist>,
+        pub cover_uri: Option<String>,
+        pub description: Option<String>,
+        pub short_description: Option<String>,
+        #[serde(default)]
+        pub volumes: Vec<Vec<PodcastEpisode>>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PodcastEpisode {
+        pub id: u64,
+        pub title: String,
+        pub duration_ms: usize,
+        pub short_description: Option<String>,
+        pub pub_date: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TrackDownloadInfo {
+        pub codec: String,
+        pub bitrate_in_kbps: u16,
+        pub download_info_url: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct DownloadLocation {
+        pub host: String,
+        pub path: String,
+        pub ts: String,
+        pub s: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct StationItem {
+        pub station: Station,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Station {
+        pub id: StationKey,
+        pub name: String,
+        #[serde(default)]
+        pub description: String,
+        pub icon: Option<StationIcon>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct StationKey {
+        #[serde(rename = "type")]
+        pub station_type: String,
+        pub tag: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StationIcon {
+        pub image_url: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct AccountStatus {
+        pub account: Account,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Account {
+        pub uid: Option<u64>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct LikedTracksResponse {
+        pub library: Library,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Library {
+        #[serde(default)]
+        pub tracks: Vec<TrackShort>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct TrackShort {
+        pub id: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct LikedAlbum {
+        pub album: Album,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StationTracksResponse {
+        pub sequence: Vec<StationTrack>,
+        #[serde(alias = "batchId")]
+        pub radio_session_id: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct StationTrack {
+        pub track: Track,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Results<T> {
+        pub results: Vec<T>,
+        pub total: Option<usize>,
     }
 
     #[derive(Debug, Deserialize)]
@@ -162,47 +922,260 @@ mod data {
         pub title: String,
         pub duration_ms: usize,
         pub artists: Vec<Artist>,
+        // Missing for uploaded and some unavailable tracks
+        #[serde(default)]
         pub albums: Vec<Album>,
         pub cover_uri: String,
     }
 
     #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
     pub struct Album {
         pub id: u64,
+        #[serde(default)]
+        pub title: String,
+        #[serde(default)]
+        pub artists: Vec<Artist>,
+        pub cover_uri: Option<String>,
+        pub year: Option<u32>,
+        pub release_date: Option<String>,
+        pub track_count: Option<usize>,
+        pub track_position: Option<TrackPosition>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct AlbumWithTracks {
+        #[serde(flatten)]
+        pub album: Album,
+        #[serde(default)]
+        pub volumes: Vec<Vec<Track>>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct TrackPosition {
+        pub index: u32,
     }
 
     #[derive(Debug, Deserialize)]
     pub struct Artist {
         pub id: u64,
         pub name: String,
+        pub cover: Option<Cover>,
+        #[serde(default)]
+        pub genres: Vec<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Cover {
+        pub uri: Option<String>,
     }
 
-    impl From<ApiResponse<SearchResponse>> for SearchResults {
-        fn from(value: ApiResponse<SearchResponse>) -> Self {
+    impl From<SearchResponse> for SearchResults {
+        fn from(value: SearchResponse) -> Self {
             Self {
-                tracks: value
-                    .result
-                    .tracks
-                    .results
-                    .into_iter()
-                    .map(Track::into)
-                    .collect(),
+                total: value.tracks.total,
+                tracks: value.tracks.results.into_iter().map(Track::into).collect(),
+                cache_hit: false,
+            }
+        }
+    }
+
+    impl From<SearchAllResponse> for crate::yandex::UnifiedSearchResults {
+        fn from(value: SearchAllResponse) -> Self {
+            fn results<T, U: From<T>>(results: Option<Results<T>>) -> Vec<U> {
+                results
+                    .map(|r| r.results.into_iter().map(U::from).collect())
+                    .unwrap_or_default()
+            }
+
+            Self {
+                tracks: results(value.tracks),
+                albums: results(value.albums),
+                artists: results(value.artists),
+                playlists: results(value.playlists),
+            }
+        }
+    }
+
+    impl From<Playlist> for crate::yandex::PlaylistInfo {
+        fn from(value: Playlist) -> Self {
+            let owner = value
+                .owner
+                .map(|o| o.login)
+                .unwrap_or_else(|| value.uid.to_string());
+            Self {
+                id: format!("{}:{}", value.uid, value.kind),
+                url: format!(
+                    "https://music.yandex.ru/users/{}/playlists/{}",
+                    owner, value.kind
+                ),
+                name: value.title,
+                track_count: value.track_count,
+                cover_url: value
+                    .cover
+                    .and_then(|c| c.uri)
+                    .map(|uri| resolve_cover_uri(&uri, ORIGINAL_COVER_SIZE)),
             }
         }
     }
 
     impl From<Track> for crate::Track {
         fn from(value: Track) -> Self {
+            let album = value.albums.first();
+            Self {
+                id: value.id.to_string(),
+                url: match album.map(|a| a.id) {
+                    Some(album_id) => format!(
+                        "https://music.yandex.ru/album/{}/track/{}",
+                        album_id, value.id
+                    ),
+                    None => format!("https://music.yandex.ru/track/{}", value.id),
+                },
+                title: value.title,
+                duration_ms: value.duration_ms,
+                artists: value.artists.into_iter().map(Artist::into).collect(),
+                cover_url: resolve_cover_uri(&value.cover_uri, ORIGINAL_COVER_SIZE),
+                cover_uri_template: Some(normalize_cover_url_template(&value.cover_uri)),
+                isrc: None,
+                album_label_id: None,
+                album: album.map(|a| a.title.clone()).filter(|t| !t.is_empty()),
+                track_number: album
+                    .and_then(|a| a.track_position.as_ref())
+                    .map(|p| p.index),
+            }
+        }
+    }
+
+    impl From<StationItem> for crate::yandex::Station {
+        fn from(value: StationItem) -> Self {
+            let station = value.station;
+            Self {
+                id: crate::id::StationId::new(&station.id.station_type, &station.id.tag),
+                name: station.name,
+                description: station.description,
+                cover_url: station
+                    .icon
+                    .and_then(|icon| icon.image_url)
+                    .map(|uri| resolve_cover_uri(&uri, ORIGINAL_COVER_SIZE)),
+            }
+        }
+    }
+
+    impl From<StationTracksResponse> for crate::yandex::StationResponse {
+        fn from(value: StationTracksResponse) -> Self {
+            Self {
+                tracks: value.sequence.into_iter().map(|t| t.track.into()).collect(),
+                radio_session_id: value.radio_session_id,
+            }
+        }
+    }
+
+    impl From<PodcastAlbum> for crate::yandex::Podcast {
+        fn from(value: PodcastAlbum) -> Self {
+            Self {
+                id: value.id.to_string(),
+                title: value.title,
+                author: value
+                    .artists
+                    .into_iter()
+                    .map(|a| a.name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                description: value.description.or(value.short_description),
+                cover_url: value
+                    .cover_uri
+                    .map(|uri| resolve_cover_uri(&uri, ORIGINAL_COVER_SIZE))
+                    .unwrap_or_default(),
+                episodes: value
+                    .volumes
+                    .into_iter()
+                    .flatten()
+                    .map(PodcastEpisode::into)
+                    .collect(),
+            }
+        }
+    }
+
+    impl From<PodcastEpisode> for crate::yandex::PodcastEpisode {
+        fn from(value: PodcastEpisode) -> Self {
             Self {
                 id: value.id.to_string(),
-                url: format!(
-                    "https://music.yandex.ru/album/{}/track/{}",
-                    value.albums[0].id, value.id
-                ),
                 title: value.title,
+                description: value.short_description,
                 duration_ms: value.duration_ms,
+                published_at: value.pub_date,
+                audio_url: None,
+            }
+        }
+    }
+
+    impl From<DownloadLocation> for String {
+        fn from(value: DownloadLocation) -> Self {
+            let path = value.path.strip_prefix('/').unwrap_or(&value.path);
+            let mut hasher = Md5::new();
+            hasher.update(format!("{}{}{}", DOWNLOAD_SALT, path, value.s));
+            format!(
+                "https://{}/get-mp3/{:x}/{}/{}",
+                value.host,
+                hasher.finalize(),
+                value.ts,
+                path
+            )
+        }
+    }
+
+    impl From<LyricsData> for crate::lyrics::LyricsResult {
+        fn from(value: LyricsData) -> Self {
+            Self::new(value.full_lyrics, value.sync_lyrics.as_deref())
+        }
+    }
+
+    impl From<ChartTrack> for crate::yandex::ChartEntry {
+        fn from(value: ChartTrack) -> Self {
+            Self {
+                position: value.chart.position,
+                track: value.track.into(),
+            }
+        }
+    }
+
+    impl From<Album> for crate::Album {
+        fn from(value: Album) -> Self {
+            Self {
+                id: value.id.to_string(),
+                url: format!("https://music.yandex.ru/album/{}", value.id),
+                title: value.title,
                 artists: value.artists.into_iter().map(Artist::into).collect(),
-                cover_url: format!("https://{}", value.cover_uri.replace("%%", "orig")),
+                cover_url: value
+                    .cover_uri
+                    .map(|uri| resolve_cover_uri(&uri, ORIGINAL_COVER_SIZE))
+                    .unwrap_or_default(),
+                release_date: value
+                    .release_date
+                    .or_else(|| value.year.map(|year| year.to_string())),
+                track_count: value.track_count.unwrap_or_default(),
+                disc_count: None,
+                max_sampling_rate_khz: None,
+                max_bit_depth: None,
+                tracks: vec![],
+            }
+        }
+    }
+
+    impl From<AlbumWithTracks> for crate::Album {
+        fn from(value: AlbumWithTracks) -> Self {
+            let disc_count = value.volumes.len();
+            let tracks = value
+                .volumes
+                .into_iter()
+                .flatten()
+                .map(Track::into)
+                .collect::<Vec<crate::Track>>();
+            Self {
+                track_count: tracks.len(),
+                disc_count: Some(disc_count),
+                tracks,
+                ..value.album.into()
             }
         }
     }
@@ -215,27 +1188,250 @@ mod data {
             }
         }
     }
+
+    impl From<Artist> for crate::ArtistDetail {
+        fn from(value: Artist) -> Self {
+            Self {
+                id: value.id.to_string(),
+                url: format!("https://music.yandex.ru/artist/{}", value.id),
+                name: value.name,
+                cover_url: value
+                    .cover
+                    .and_then(|c| c.uri)
+                    .map(|uri| resolve_cover_uri(&uri, ORIGINAL_COVER_SIZE)),
+                genres: value.genres,
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::{
-        save_audio_stream,
-        yandex::{Config, Yandex},
+        AudioFormat,
+        id::YandexTrackId,
+        no_op_progress, save_audio_stream,
+        yandex::{
+            Config, Podcast, UnifiedSearchResults, UserLibrary, Yandex, data, resolve_cover_uri,
+        },
     };
     use std::path::Path;
 
+    #[test]
+    fn podcast_response() {
+        let album: data::PodcastAlbum = serde_json::from_str(
+            r#"{
+                "id": 1,
+                "title": "Podcast",
+                "type": "podcast",
+                "artists": [{"id": 2, "name": "Author"}],
+                "coverUri": "avatars.yandex.net/%%",
+                "shortDescription": "About",
+                "volumes": [[{
+                    "id": 3,
+                    "title": "Episode",
+                    "durationMs": 60000,
+                    "shortDescription": "Episode description",
+                    "pubDate": "2025-01-01T00:00:00+00:00"
+                }]]
+            }"#,
+        )
+        .unwrap();
+        let podcast: Podcast = album.into();
+        assert_eq!(podcast.author, "Author");
+        assert_eq!(podcast.description.as_deref(), Some("About"));
+        assert_eq!(podcast.cover_url, "https://avatars.yandex.net/orig");
+
+        let metadata = podcast.episodes[0].metadata(&podcast);
+        assert_eq!(metadata.album.as_deref(), Some("Podcast"));
+        assert_eq!(metadata.comment.as_deref(), Some("Episode description"));
+
+        let url: String = data::DownloadLocation {
+            host: "host".to_owned(),
+            path: "/path".to_owned(),
+            ts: "ts".to_owned(),
+            s: "s".to_owned(),
+        }
+        .into();
+        assert_eq!(
+            url,
+            "https://host/get-mp3/2aa40d70e325fef5c5bd5141ed1651f6/ts/path"
+        );
+    }
+
+    #[test]
+    fn library_response() {
+        let status: data::AccountStatus =
+            serde_json::from_str(r#"{"account": {"uid": 42, "login": "user"}}"#).unwrap();
+        assert_eq!(status.account.uid, Some(42));
+
+        let likes: data::LikedTracksResponse = serde_json::from_str(
+            r#"{"library": {"uid": 42, "revision": 7, "tracks": [
+                {"id": "1", "albumId": "10", "timestamp": "2025-01-01T00:00:00+00:00"},
+                {"id": "2", "albumId": "20", "timestamp": "2025-01-02T00:00:00+00:00"}
+            ]}}"#,
+        )
+        .unwrap();
+        assert_eq!(likes.library.tracks.len(), 2);
+        assert_eq!(likes.library.tracks[1].id, "2");
+
+        let library = UserLibrary {
+            tracks: vec![crate::Track::example()],
+            ..Default::default()
+        };
+        assert_eq!((library.track_count(), library.album_count()), (1, 0));
+    }
+
+    #[test]
+    fn track_without_album() {
+        let track: data::Track = serde_json::from_str(
+            r#"{
+                "id": 5,
+                "title": "Upload",
+                "durationMs": 1000,
+                "artists": [],
+                "coverUri": "avatars.yandex.net/%%"
+            }"#,
+        )
+        .unwrap();
+        let track = crate::Track::from(track);
+        assert_eq!(track.url, "https://music.yandex.ru/track/5");
+        assert_eq!(track.album, None);
+        assert_eq!(
+            YandexTrackId::try_from(track.url.as_str())
+                .unwrap()
+                .as_str(),
+            "5"
+        );
+    }
+
+    #[test]
+    fn search_all_response() {
+        let response: data::SearchAllResponse = serde_json::from_str(
+            r#"{
+                "tracks": {"total": 1, "results": [{
+                    "id": 1,
+                    "title": "Track",
+                    "durationMs": 60000,
+                    "artists": [{"id": 2, "name": "Artist"}],
+                    "albums": [{"id": 3, "title": "Album"}],
+                    "coverUri": "avatars.yandex.net/%%"
+                }]},
+                "artists": {"total": 1, "results": [{"id": 2, "name": "Artist"}]},
+                "playlists": {"total": 1, "results": [{
+                    "uid": 4,
+                    "kind": 1000,
+                    "title": "Playlist",
+                    "trackCount": 25,
+                    "owner": {"uid": 4, "login": "user"},
+                    "cover": {"type": "pic", "uri": "avatars.yandex.net/playlist/%%"}
+                }]}
+            }"#,
+        )
+        .unwrap();
+        let results = UnifiedSearchResults::from(response);
+        assert!(results.albums.is_empty());
+        assert_eq!(results.artists[0].name, "Artist");
+        let playlist = &results.playlists[0];
+        assert_eq!(playlist.id, "4:1000");
+        assert_eq!(
+            playlist.url,
+            "https://music.yandex.ru/users/user/playlists/1000"
+        );
+        assert_eq!(playlist.track_count, 25);
+        assert_eq!(
+            playlist.cover_url.as_deref(),
+            Some("https://avatars.yandex.net/playlist/orig")
+        );
+
+        let results = results.into_search_results();
+        assert_eq!(results.tracks.len(), 1);
+        assert_eq!(results[0].album.as_deref(), Some("Album"));
+    }
+
+    #[test]
+    fn album_response() {
+        let album: crate::Album = serde_json::from_str::<data::AlbumWithTracks>(
+            r#"{
+                "id": 3,
+                "title": "Album",
+                "artists": [{"id": 2, "name": "Artist"}],
+                "coverUri": "avatars.yandex.net/album/%%",
+                "releaseDate": "2025-01-01T00:00:00+03:00",
+                "trackCount": 2,
+                "volumes": [[{
+                    "id": 1,
+                    "title": "First",
+                    "durationMs": 60000,
+                    "artists": [{"id": 2, "name": "Artist"}],
+                    "albums": [{"id": 3, "title": "Album", "trackPosition": {"volume": 1, "index": 1}}],
+                    "coverUri": "avatars.yandex.net/album/%%"
+                }], [{
+                    "id": 4,
+                    "title": "Second",
+                    "durationMs": 30000,
+                    "artists": [{"id": 2, "name": "Artist"}],
+                    "albums": [{"id": 3, "title": "Album", "trackPosition": {"volume": 2, "index": 1}}],
+                    "coverUri": "avatars.yandex.net/album/%%"
+                }]]
+            }"#,
+        )
+        .unwrap()
+        .into();
+        assert_eq!(album.url, "https://music.yandex.ru/album/3");
+        assert_eq!(album.cover_url, "https://avatars.yandex.net/album/orig");
+        assert_eq!(
+            album.release_date.as_deref(),
+            Some("2025-01-01T00:00:00+03:00")
+        );
+        assert_eq!((album.track_count, album.disc_count), (2, Some(2)));
+        assert_eq!(
+            album.tracks[1].url,
+            "https://music.yandex.ru/album/3/track/4"
+        );
+        assert_eq!(album.total_duration_ms(), 90000);
+
+        assert_eq!(
+            resolve_cover_uri("avatars.yandex.net/%%", 400),
+            "https://avatars.yandex.net/400x400"
+        );
+    }
+
+    #[test]
+    fn file_info_response() {
+        let response: data::GetFileInfoResponse = serde_json::from_str(
+            r#"{"downloadInfo": {
+                "codec": "aac-mp4",
+                "bitrate": 256,
+                "url": "https://example.com/track.m4a",
+                "size": 7340032
+            }}"#,
+        )
+        .unwrap();
+        let info = response.download_info;
+        assert_eq!(info.format().unwrap(), AudioFormat::Aac(256));
+        assert_eq!(info.size, Some(7340032));
+
+        let info = data::DownloadInfo {
+            codec: "opus".to_owned(),
+            ..info
+        };
+        assert!(info.format().is_err());
+    }
+
     #[tokio::test]
     async fn all() {
         let query =
             std::env::var("FRUITYGER_YANDEX_QUERY").unwrap_or("periphery scarlet".to_string());
-        let client = Yandex::new(Config {
-            token: std::env::var("FRUITYGER_YANDEX_TOKEN")
-                .expect("FRUITYGER_YANDEX_TOKEN is required to test this module"),
-        });
+        let client = Yandex::new(
+            Config::from_env().expect("FRUITYGER_YANDEX_TOKEN is required to test this module"),
+        );
         let results = client.search(&query, 0).await.unwrap();
-        let stream = client.get_stream(&results.tracks[0].id).await.unwrap();
-        save_audio_stream(stream, Path::new("/tmp"), "yandex_test")
+        let stream = client
+            .get_stream(&YandexTrackId::try_from(results.tracks[0].id.as_str()).unwrap())
+            .await
+            .unwrap();
+        save_audio_stream(stream, Path::new("/tmp"), "yandex_test", no_op_progress)
             .await
             .unwrap();
     }
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::path::Path;
+
+use wiremock::ResponseTemplate;
+
+/// Reads `tests/fixtures/{name}`, URLs in fixtures point at `{{base_url}}`
+pub fn fixture(name: &str, base_url: &str) -> String {
+    std::fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name),
+    )
+    .unwrap()
+    .replace("{{base_url}}", base_url)
+}
+
+pub fn json_fixture(name: &str, base_url: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_raw(fixture(name, base_url), "application/json")
+}
@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+mod common;
+
+use fruityger::{
+    error::Error,
+    format::{AudioFormat, HifiQuality},
+    hifi::{CircuitBreakerConfig, CircuitStatus, Config, Hifi, Host},
+    id::HifiTrackId,
+    retry::RetryPolicy,
+};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, path, query_param},
+};
+
+use common::json_fixture;
+
+#[tokio::test]
+async fn search() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/search/"))
+        .and(query_param("s", "periphery scarlet"))
+        .respond_with(json_fixture("hifi_search_response.json", &server.uri()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Hifi::new(Config::new(vec![Host::new(server.uri())]));
+    let results = client.search("periphery scarlet", 0).await.unwrap();
+    let track = &results[0];
+    assert_eq!(track.id, "60207960");
+    assert_eq!(track.duration_ms, 257_000);
+    assert_eq!(
+        track.album.as_deref(),
+        Some("Periphery III: Select Difficulty")
+    );
+    assert_eq!(track.track_number, Some(2));
+    assert_eq!(results.total, Some(1));
+}
+
+#[tokio::test]
+async fn pagination() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/search/"))
+        .and(query_param("offset", "40"))
+        .and(query_param("limit", "20"))
+        .respond_with(json_fixture("hifi_search_response.json", &server.uri()))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/search/"))
+        .and(query_param("offset", "10"))
+        .and(query_param("limit", "5"))
+        .respond_with(json_fixture("hifi_search_response.json", &server.uri()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Hifi::new(Config::new(vec![Host::new(server.uri())]));
+    client.search("scarlet", 2).await.unwrap();
+
+    let client = Hifi::new(Config {
+        limit: 5,
+        ..Config::new(vec![Host::new(server.uri())])
+    });
+    client.search("scarlet", 2).await.unwrap();
+}
+
+#[tokio::test]
+async fn search_by_isrc() {
+    let server = MockServer::start().await;
+    // The host ignores isrc and answers without matches
+    Mock::given(method("GET"))
+        .and(path("/search/"))
+        .and(query_param("isrc", "USSM11600823"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "limit": 20, "offset": 0, "totalNumberOfItems": 0, "items": []
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/search/"))
+        .and(query_param("s", "scarlet periphery"))
+        .respond_with(json_fixture("hifi_search_response.json", &server.uri()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Hifi::new(Config::new(vec![Host::new(server.uri())]));
+    let track = client
+        .search_by_isrc("USSM11600823", Some("scarlet periphery"))
+        .await
+        .unwrap();
+    assert_eq!(track.unwrap().id, "60207960");
+}
+
+#[tokio::test]
+async fn search_by_isrc_circuit_breaker() {
+    let server = MockServer::start().await;
+    Mock::given(path("/search/"))
+        .and(query_param("isrc", "USSM11600823"))
+        .respond_with(ResponseTemplate::new(400))
+        .mount(&server)
+        .await;
+    Mock::given(path("/search/"))
+        .and(query_param("s", "scarlet periphery"))
+        .respond_with(json_fixture("hifi_search_response.json", &server.uri()))
+        .mount(&server)
+        .await;
+
+    // Rejected ISRC lookups do not open the circuit of a healthy host
+    let client = Hifi::with_circuit_breaker(
+        Config::new(vec![Host::new(server.uri())]),
+        CircuitBreakerConfig {
+            failure_threshold: 2,
+            ..Default::default()
+        },
+    );
+    for _ in 0..3 {
+        let track = client
+            .search_by_isrc("USSM11600823", Some("scarlet periphery"))
+            .await
+            .unwrap();
+        assert!(track.is_some());
+    }
+    assert_eq!(
+        client.circuit_status(&server.uri()),
+        Some(CircuitStatus::Closed)
+    );
+}
+
+#[tokio::test]
+async fn get_stream() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/track/"))
+        .and(query_param("id", "60207960"))
+        .and(query_param("quality", "LOSSLESS"))
+        .respond_with(json_fixture("hifi_track_response.json", &server.uri()))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/file/60207960.flac"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"fLaC".as_slice()))
+        .mount(&server)
+        .await;
+
+    let client = Hifi::new(Config::new(vec![Host::new(server.uri())]));
+    let stream = client
+        .get_stream(
+            &HifiTrackId::try_from("60207960").unwrap(),
+            HifiQuality::Lossless,
+        )
+        .await
+        .unwrap();
+    assert_eq!(stream.format, AudioFormat::Flac);
+    assert_eq!(stream.response.bytes().await.unwrap(), "fLaC");
+}
+
+#[tokio::test]
+async fn host_fallback() {
+    let down = MockServer::start().await;
+    Mock::given(path("/search/"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(2)
+        .mount(&down)
+        .await;
+    let up = MockServer::start().await;
+    Mock::given(path("/search/"))
+        .respond_with(json_fixture("hifi_search_response.json", &up.uri()))
+        .expect(1)
+        .mount(&up)
+        .await;
+
+    let config = |hosts| Config {
+        retry: RetryPolicy::none(),
+        ..Config::new(hosts)
+    };
+    let client = Hifi::new(config(vec![Host::new(down.uri()), Host::new(up.uri())]));
+    assert_eq!(client.search("scarlet", 0).await.unwrap().tracks.len(), 1);
+
+    // Every host failing is reported as a service error
+    let client = Hifi::new(config(vec![Host::new(down.uri())]));
+    assert!(matches!(
+        client.search("scarlet", 0).await,
+        Err(Error::ServiceError(_))
+    ));
+}
+
+#[tokio::test]
+async fn circuit_breaker_client_errors() {
+    let server = MockServer::start().await;
+    Mock::given(path("/search/"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    // A host answering with client errors keeps its circuit closed
+    let client = Hifi::with_circuit_breaker(
+        Config::new(vec![Host::new(server.uri())]),
+        CircuitBreakerConfig {
+            failure_threshold: 2,
+            ..Default::default()
+        },
+    );
+    for _ in 0..3 {
+        assert!(matches!(
+            client.search("missing", 0).await,
+            Err(Error::ServiceError(_))
+        ));
+    }
+    assert_eq!(
+        client.circuit_status(&server.uri()),
+        Some(CircuitStatus::Closed)
+    );
+}
+
+#[tokio::test]
+async fn errors() {
+    let server = MockServer::start().await;
+    Mock::given(path("/search/"))
+        .and(query_param("s", "unauthorized"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+    Mock::given(path("/search/"))
+        .and(query_param("s", "rate limited"))
+        .respond_with(ResponseTemplate::new(429))
+        .mount(&server)
+        .await;
+
+    let client = Hifi::new(Config {
+        retry: RetryPolicy::none(),
+        ..Config::new(vec![Host::new(server.uri())])
+    });
+    assert!(matches!(
+        client.search("unauthorized", 0).await,
+        Err(Error::AuthenticationError(_))
+    ));
+    assert!(matches!(
+        client.search("rate limited", 0).await,
+        Err(Error::RateLimitError(_))
+    ));
+}
@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+mod common;
+
+use fruityger::{
+    error::Error,
+    format::AudioFormat,
+    id::QobuzTrackId,
+    qobuz::{Config, Qobuz},
+    retry::RetryPolicy,
+};
+use serde_json::json;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{header, method, path, query_param},
+};
+
+use common::json_fixture;
+
+async fn setup() -> (MockServer, Qobuz) {
+    let server = MockServer::start().await;
+    let client = Qobuz::with_base_url(Config::new("token", "app", "secret"), server.uri());
+    (server, client)
+}
+
+#[tokio::test]
+async fn search() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/api.json/0.2/catalog/search"))
+        .and(query_param("query", "periphery scarlet"))
+        .and(query_param("type", "tracks"))
+        .and(query_param("app_id", "app"))
+        .and(header("x-user-auth-token", "token"))
+        .respond_with(json_fixture("qobuz_search_response.json", &server.uri()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results = client.search("periphery scarlet", 0).await.unwrap();
+    let track = &results[0];
+    assert_eq!(track.id, "59954847");
+    assert_eq!(track.title, "Scarlet");
+    assert_eq!(track.duration_ms, 257_000);
+    assert_eq!(track.artists[0].name, "Periphery");
+    assert_eq!(track.isrc.as_deref(), Some("USSM11600823"));
+    assert_eq!(track.track_number, Some(2));
+}
+
+#[tokio::test]
+async fn search_albums() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/api.json/0.2/catalog/search"))
+        .and(query_param("query", "periphery"))
+        .and(query_param("type", "albums"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "query": "periphery",
+            "albums": {"limit": 20, "offset": 0, "total": 1, "items": [{
+                "id": "0886446016683",
+                "title": "Periphery III: Select Difficulty",
+                "artist": {"id": 465316, "name": "Periphery"},
+                "tracks_count": 11,
+                "image": {"large": "https://static.qobuz.com/images/covers/83/66/0886446016683_600.jpg"},
+                "maximum_sampling_rate": 44.1,
+                "maximum_bit_depth": 16
+            }]}
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let albums = client.search_albums("periphery", 0).await.unwrap();
+    assert_eq!(albums[0].id, "0886446016683");
+    assert_eq!(albums[0].track_count, 11);
+    assert_eq!(albums[0].max_bit_depth, Some(16));
+}
+
+#[tokio::test]
+async fn get_stream() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/api.json/0.2/track/getFileUrl"))
+        .and(query_param("track_id", "59954847"))
+        .respond_with(json_fixture("qobuz_file_url_response.json", &server.uri()))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/file/59954847.flac"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"fLaC".as_slice()))
+        .mount(&server)
+        .await;
+
+    let stream = client
+        .get_stream(&QobuzTrackId::try_from("59954847").unwrap())
+        .await
+        .unwrap();
+    assert_eq!(stream.format, AudioFormat::Flac);
+    assert_eq!(
+        stream.md5.as_deref(),
+        Some("2d58d5c3c1c8f3e8e6b2aa5f0c8b4d55")
+    );
+    assert_eq!(stream.response.bytes().await.unwrap(), "fLaC");
+}
+
+#[tokio::test]
+async fn get_label_new_releases() {
+    let (server, client) = setup().await;
+    let feed = |offset: usize, count: usize| {
+        let items = (offset..offset + count)
+            .map(|i| {
+                json!({
+                    "id": i.to_string(),
+                    "title": format!("Album {}", i),
+                    "image": {"large": "https://example.com/cover.jpg"},
+                    // Every other release is on the requested label
+                    "label": {"id": if i % 2 == 0 { 1 } else { 2 }}
+                })
+            })
+            .collect::<Vec<_>>();
+        ResponseTemplate::new(200).set_body_json(json!({"albums": {"items": items}}))
+    };
+    for (offset, count) in [(0, 20), (20, 20), (40, 5)] {
+        Mock::given(path("/api.json/0.2/catalog/getFeatured"))
+            .and(query_param("type", "new-releases"))
+            .and(query_param("label_id", "1"))
+            .and(query_param("offset", offset.to_string()))
+            .respond_with(feed(offset, count))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    // The first 20 matches fill page 0, page 1 gets what the feed has left
+    let albums = client.get_label_new_releases("1", 1).await.unwrap();
+    let ids = albums.iter().map(|a| a.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(ids, ["40", "42", "44"]);
+
+    // A label missing from an endless feed stops after 10 feed pages
+    Mock::given(path("/api.json/0.2/catalog/getFeatured"))
+        .and(query_param("label_id", "3"))
+        .respond_with(feed(0, 20))
+        .expect(10)
+        .mount(&server)
+        .await;
+    assert!(
+        client
+            .get_label_new_releases("3", 0)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn errors() {
+    let server = MockServer::start().await;
+    // The server error would be retried with backoff otherwise
+    let client = Qobuz::with_base_url(
+        Config::new("token", "app", "secret").with_retry_policy(RetryPolicy::none()),
+        server.uri(),
+    );
+    Mock::given(path("/api.json/0.2/catalog/search"))
+        .and(query_param("query", "unauthorized"))
+        .respond_with(ResponseTemplate::new(401).set_body_raw(
+            r#"{"status":"error","code":401,"message":"User authentication is required."}"#,
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(path("/api.json/0.2/catalog/search"))
+        .and(query_param("query", "rate limited"))
+        .respond_with(ResponseTemplate::new(429).set_body_string("Too Many Requests"))
+        .mount(&server)
+        .await;
+    Mock::given(path("/api.json/0.2/catalog/search"))
+        .and(query_param("query", "payment"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            r#"{"status":"error","code":402,"message":"Subscription required"}"#,
+            "application/json",
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(path("/api.json/0.2/catalog/search"))
+        .and(query_param("query", "broken"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(
+        client.search("unauthorized", 0).await,
+        Err(Error::AuthenticationError(message)) if message == "User authentication is required."
+    ));
+    assert!(matches!(
+        client.search("rate limited", 0).await,
+        Err(Error::RateLimitError(_))
+    ));
+    assert!(matches!(
+        client.search("payment", 0).await,
+        Err(Error::PaymentRequiredError(_))
+    ));
+    assert!(matches!(
+        client.search("broken", 0).await,
+        Err(Error::ServiceError(_))
+    ));
+}
@@ -0,0 +1,253 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+mod common;
+
+use fruityger::{
+    error::Error,
+    format::AudioFormat,
+    id::YandexTrackId,
+    retry::RetryPolicy,
+    yandex::{Config, Yandex},
+};
+use serde_json::json;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{body_string_contains, header, method, path, query_param},
+};
+
+use common::json_fixture;
+
+async fn setup() -> (MockServer, Yandex) {
+    let server = MockServer::start().await;
+    let config = Config::new("token").with_retry_policy(RetryPolicy {
+        initial_delay_ms: 1,
+        ..Default::default()
+    });
+    let client = Yandex::with_base_url(config, server.uri());
+    (server, client)
+}
+
+#[tokio::test]
+async fn search() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/search"))
+        .and(query_param("text", "periphery scarlet"))
+        .and(query_param("type", "track"))
+        .and(header("authorization", "OAuth token"))
+        .respond_with(json_fixture("yandex_search_response.json", &server.uri()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results = client.search("periphery scarlet", 0).await.unwrap();
+    let track = &results[0];
+    assert_eq!(track.id, "26637632");
+    assert_eq!(
+        track.url,
+        "https://music.yandex.ru/album/3685349/track/26637632"
+    );
+    assert_eq!(track.artists[0].name, "Periphery");
+    assert_eq!(track.track_number, Some(2));
+    assert_eq!(
+        track.cover_url,
+        "https://avatars.yandex.net/get-music-content/49876/4b1b7ed8.a.3685349-1/orig"
+    );
+}
+
+#[tokio::test]
+async fn get_stream() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/get-file-info"))
+        .and(query_param("trackId", "26637632"))
+        .respond_with(json_fixture(
+            "yandex_file_info_response.json",
+            &server.uri(),
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/file/26637632.mp4"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(b"ftyp".as_slice()))
+        .mount(&server)
+        .await;
+
+    let id = YandexTrackId::try_from("26637632").unwrap();
+    let stream = client.get_stream(&id).await.unwrap();
+    assert_eq!(stream.format, AudioFormat::FlacMp4);
+    assert_eq!(stream.response.bytes().await.unwrap(), "ftyp");
+
+    let options = client.get_download_options(&id).await.unwrap();
+    assert_eq!(options.len(), 1);
+    assert_eq!(options[0].filesize_bytes, Some(28311552));
+}
+
+#[tokio::test]
+async fn user_library() {
+    let (server, client) = setup().await;
+    Mock::given(path("/account/status"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({"result": {"account": {"uid": 42}}})),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/users/42/likes/tracks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"result": {
+            "library": {"uid": 42, "tracks": [{"id": "26637632", "albumId": "3685349"}]}
+        }})))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/tracks"))
+        .and(body_string_contains("track-ids=26637632"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"result": [{
+            "id": 26637632,
+            "title": "Scarlet",
+            "durationMs": 257000,
+            "coverUri": "avatars.yandex.net/%%",
+            "artists": [{"id": 1155410, "name": "Periphery"}],
+            "albums": [{"id": 3685349}]
+        }]})))
+        .mount(&server)
+        .await;
+    Mock::given(path("/users/42/likes/albums"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"result": [
+            {"album": {"id": 3685349, "title": "Periphery III: Select Difficulty"}}
+        ]})))
+        .mount(&server)
+        .await;
+    Mock::given(path("/users/42/likes/artists"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"result": [
+            {"id": 1155410, "name": "Periphery"}
+        ]})))
+        .mount(&server)
+        .await;
+
+    let library = client.get_user_library().await.unwrap();
+    assert_eq!(library.track_count(), 1);
+    assert_eq!(library.album_count(), 1);
+    assert_eq!(library.artists[0].name, "Periphery");
+    // The user id is only looked up once
+    client.get_user_library().await.unwrap();
+}
+
+#[tokio::test]
+async fn get_lyrics() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/tracks/26637632/lyrics"))
+        .and(query_param("format", "TEXT"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": {"downloadUrl": format!("{}/lyrics/26637632.txt", server.uri())}
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/tracks/26637632/lyrics"))
+        .and(query_param("format", "LRC"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": {"downloadUrl": format!("{}/lyrics/26637632.lrc", server.uri())}
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/lyrics/26637632.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("First line"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/lyrics/26637632.lrc"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[00:01.00] First line"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/tracks/1/lyrics"))
+        .and(query_param("format", "TEXT"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "result": {"downloadUrl": format!("{}/lyrics/26637632.txt", server.uri())}
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/tracks/1/lyrics"))
+        .and(query_param("format", "LRC"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let lyrics = client.get_lyrics("26637632").await.unwrap();
+    assert_eq!(lyrics.text, "First line");
+    assert!(lyrics.has_sync);
+    assert_eq!(lyrics.sync_data.as_deref(), Some("[00:01.00] First line"));
+
+    let lyrics = client.get_lyrics("1").await.unwrap();
+    assert!(!lyrics.has_sync);
+    assert_eq!(lyrics.sync_data, None);
+}
+
+#[tokio::test]
+async fn errors() {
+    let (server, client) = setup().await;
+    Mock::given(path("/search"))
+        .and(query_param("text", "unauthorized"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+    Mock::given(path("/search"))
+        .and(query_param("text", "rate limited"))
+        .respond_with(ResponseTemplate::new(429))
+        .mount(&server)
+        .await;
+    Mock::given(path("/search"))
+        .and(query_param("text", "broken"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(
+        client.search("unauthorized", 0).await,
+        Err(Error::AuthenticationError(_))
+    ));
+    assert!(matches!(
+        client.search("rate limited", 0).await,
+        Err(Error::RateLimitError(_))
+    ));
+    assert!(matches!(
+        client.search("broken", 0).await,
+        Err(Error::ServiceError(_))
+    ));
+}
+
+#[tokio::test]
+async fn retry() {
+    let (server, client) = setup().await;
+    Mock::given(path("/search"))
+        .and(query_param("text", "periphery scarlet"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .expect(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/search"))
+        .and(query_param("text", "periphery scarlet"))
+        .respond_with(json_fixture("yandex_search_response.json", &server.uri()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results = client.search("periphery scarlet", 0).await.unwrap();
+    assert_eq!(results.tracks.len(), 1);
+
+    // Client errors are not retried
+    Mock::given(path("/search"))
+        .and(query_param("text", "unauthorized"))
+        .respond_with(ResponseTemplate::new(401))
+        .expect(1)
+        .mount(&server)
+        .await;
+    assert!(client.search("unauthorized", 0).await.is_err());
+}